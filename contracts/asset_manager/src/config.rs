@@ -1,5 +1,5 @@
 use crate::storage_types::DataKey;
-use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, String};
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, String, Vec};
 
 #[derive(Clone)]
 #[contracttype]
@@ -9,6 +9,18 @@ pub struct ConfigData {
     pub native_address: Address,
     pub icon_asset_manager: String,
     pub upgrade_authority: Address,
+    pub fee_collector: Address,
+}
+
+/// Guardians that co-sign withdrawals above the approval threshold of their
+/// token. `threshold` of them have to approve one, unless `max_delay` passes
+/// first.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ApprovalConfig {
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+    pub max_delay: u64,
 }
 
 pub fn set_config(e: &Env, config: ConfigData) {
@@ -19,3 +31,11 @@ pub fn get_config(e: &Env) -> ConfigData {
     let key = DataKey::Config;
     e.storage().instance().get(&key).unwrap_optimized()
 }
+
+pub fn set_approval_config(e: &Env, config: &ApprovalConfig) {
+    e.storage().instance().set(&DataKey::ApprovalConfig, config);
+}
+
+pub fn get_approval_config(e: &Env) -> Option<ApprovalConfig> {
+    e.storage().instance().get(&DataKey::ApprovalConfig)
+}
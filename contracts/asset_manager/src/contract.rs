@@ -1,31 +1,65 @@
 use soroban_sdk::{
-    contract, contractimpl, token, Address, Bytes, BytesN, Env, String, Vec,
+    contract, contractimpl, token, Address, Bytes, BytesN, Env, String, Symbol, Vec,
 };
 mod xcall {
     soroban_sdk::contractimport!(file = "../../wasm/xcall.wasm");
 }
 use crate::errors::ContractError;
+use crate::events::{self, DepositEvent, WithdrawEvent};
+use crate::migration;
 use crate::states::{get_xcall_network_address, set_xcall_network_address};
-use crate::storage_types::TokenData;
+use crate::storage_types::{
+    Claimable, DataKey, DepositFee, DepositLimit, FeeType, PauseState, PendingAddress,
+    PendingWithdrawal, RateLimitGroup, RateLimitWindow, RecipientLimit, RecipientUsage,
+    Reconciliation, ReserveFlow, ScheduledUpgrade, TokenData, TokenDecimals, TokenStats,
+    WindowCapacity, WithdrawSimulation, MIN_UPGRADE_DELAY, PROPOSAL_EXPIRY,
+};
 use crate::{
-    config::{self, get_config, set_config, ConfigData},
+    config::{
+        self, get_approval_config, get_config, set_approval_config, set_config, ApprovalConfig,
+        ConfigData,
+    },
     states::{
-        extent_ttl, has_registry, read_administrator, read_token_data, read_tokens,
-        write_administrator, write_registry, write_token_data, write_tokens,
+        add_claimable, extent_ttl, has_registry, read_administrator, read_approval_threshold,
+        read_claimable, read_claimables, read_deposit_fee, read_deposit_limit,
+        read_exempt_recipients, read_guardian, read_last_claimable_id, read_pause_state,
+        read_pending_withdrawal, read_proposal, read_rate_limit_group, read_recipient_limit,
+        read_recipient_usage, read_rescuer, read_reserve_flow, read_scheduled_upgrade,
+        read_schema_version, read_token_data, read_token_decimals, read_token_rate_limit_group,
+        read_token_stats, read_tokens, read_upgrade_delay, read_withdrawal_approvals,
+        read_withdrawal_delay, remove_approval_threshold, remove_claimable, remove_deposit_fee,
+        remove_deposit_limit, remove_pending_withdrawal, remove_proposal, remove_rate_limit_group,
+        remove_recipient_limit, remove_reserve_flow, remove_scheduled_upgrade, remove_token,
+        remove_token_data, remove_token_decimals, remove_token_rate_limit_group,
+        remove_withdrawal_approvals, update_pending_withdrawal, write_administrator,
+        write_approval_threshold, write_deposit_fee, write_deposit_limit, write_exempt_recipients,
+        write_guardian, write_pause_state, write_pending_withdrawal, write_proposal,
+        write_rate_limit_group, write_recipient_limit, write_recipient_usage, write_registry,
+        write_rescuer, write_reserve_flow, write_scheduled_upgrade, write_schema_version,
+        write_token_data, write_token_decimals, write_token_rate_limit_group, write_token_stats,
+        write_tokens, write_upgrade_delay, write_withdrawal_approvals, write_withdrawal_delay,
     },
     storage_types::POINTS,
     xcall_manager_interface::XcallManagerClient,
 };
-use soroban_rlp::balanced::address_utils::is_valid_string_address;
+use soroban_rlp::balanced::address_utils::{
+    account_address, decode_string_address, get_network_id, is_valid_string_address,
+    strkey::StrKey,
+};
+use soroban_rlp::balanced::decimal_utils::scale_amount;
 use soroban_rlp::balanced::messages::{
-    deposit::Deposit, deposit_revert::DepositRevert, withdraw_to::WithdrawTo,
+    deposit::Deposit, deposit_batch::DepositBatch, deposit_batch_revert::DepositBatchRevert,
+    deposit_revert::DepositRevert, reserve_report::ReserveReport, withdraw_to::WithdrawTo,
 };
 
-use xcall::{AnyMessage, CallMessageWithRollback, Client, Envelope};
+use xcall::{AnyMessage, CallMessage, CallMessageWithRollback, Client, Envelope};
 
 const DEPOSIT_NAME: &str = "Deposit";
 const WITHDRAW_TO_NAME: &str = "WithdrawTo";
 const DEPOSIT_REVERT_NAME: &str = "DepositRevert";
+const DEPOSIT_BATCH_NAME: &str = "DepositBatch";
+const DEPOSIT_BATCH_REVERT_NAME: &str = "DepositBatchRevert";
+const RESERVE_REPORT_NAME: &str = "ReserveReport";
 
 #[contract]
 pub struct AssetManager;
@@ -38,8 +72,11 @@ impl AssetManager {
         }
         write_registry(&env, &registry);
         write_administrator(&env, &admin);
+        write_schema_version(&env, migration::SCHEMA_VERSION);
         Self::configure(env.clone(), config.clone());
-        set_xcall_network_address(&env, Self::xcall_client(&env, &config.xcall).get_network_address());
+        let network_address = Self::xcall_client(&env, &config.xcall).get_network_address();
+        set_xcall_network_address(&env, network_address.clone());
+        events::xcall_network_address_updated(&env, network_address);
         Ok(())
     }
 
@@ -47,22 +84,379 @@ impl AssetManager {
         get_config(&env)
     }
 
-    pub fn set_admin(env: Env, new_admin: Address) {
+    pub fn propose_admin(env: Env, new_admin: Address) {
         let admin = read_administrator(&env);
         admin.require_auth();
 
-        write_administrator(&env, &new_admin);
+        let proposal = Self::new_proposal(&env, new_admin);
+        write_proposal(&env, DataKey::PendingAdmin, &proposal);
+        events::admin_proposed(&env, proposal);
+    }
+
+    pub fn accept_admin(env: Env) -> Result<(), ContractError> {
+        let proposal = Self::take_proposal(&env, DataKey::PendingAdmin)?;
+
+        write_administrator(&env, &proposal.address);
+        events::admin_changed(&env, proposal.address);
+        Ok(())
+    }
+
+    pub fn cancel_admin_proposal(env: Env) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let proposal =
+            read_proposal(&env, DataKey::PendingAdmin).ok_or(ContractError::NoPendingProposal)?;
+        remove_proposal(&env, DataKey::PendingAdmin);
+        events::admin_proposal_cancelled(&env, proposal.address);
+        Ok(())
+    }
+
+    pub fn get_pending_admin(env: Env) -> Option<PendingAddress> {
+        read_proposal(&env, DataKey::PendingAdmin)
     }
 
     pub fn get_admin(env: Env) -> Address {
         read_administrator(&env)
     }
 
+    pub fn set_guardian(env: Env, guardian: Address) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        write_guardian(&env, &guardian);
+        events::guardian_changed(&env, guardian);
+    }
+
+    pub fn get_guardian(env: Env) -> Result<Address, ContractError> {
+        read_guardian(&env)
+    }
+
+    pub fn set_rescuer(env: Env, rescuer: Address) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        write_rescuer(&env, &rescuer);
+        events::rescuer_changed(&env, rescuer);
+    }
+
+    pub fn get_rescuer(env: Env) -> Result<Address, ContractError> {
+        read_rescuer(&env)
+    }
+
+    pub fn get_claimable(env: Env, recipient: Address, token: Address) -> u128 {
+        read_claimable(&env, &recipient, &token)
+    }
+
+    /// Lists claimables page by page, returning those with ids from `start` up
+    /// to, but excluding, `start + limit`. Ids start at 1 and claimed ones are
+    /// skipped, so a page can hold fewer than `limit` entries.
+    pub fn get_claimables(env: Env, start: u64, limit: u32) -> Vec<Claimable> {
+        read_claimables(&env, start, limit)
+    }
+
+    /// Returns the id given to the most recent claimable.
+    pub fn get_last_claimable_id(env: Env) -> u64 {
+        read_last_claimable_id(&env)
+    }
+
+    /// Pays out the tokens escrowed for `recipient` after a failed payout to `to`.
+    /// Callable by the recipient or the rescuer, unless withdrawals of `token`
+    /// are paused.
+    pub fn claim(
+        env: Env,
+        caller: Address,
+        recipient: Address,
+        token: Address,
+        to: Address,
+    ) -> Result<(), ContractError> {
+        if caller != recipient && Ok(caller.clone()) != read_rescuer(&env) {
+            return Err(ContractError::OnlyRecipientOrRescuer);
+        }
+        caller.require_auth();
+
+        let amount = read_claimable(&env, &recipient, &token);
+        if amount == 0 {
+            return Err(ContractError::NothingToClaim);
+        }
+        if Self::is_withdraw_paused(env.clone(), token.clone()) {
+            return Err(ContractError::Paused);
+        }
+        remove_claimable(&env, &recipient, &token);
+
+        Self::transfer_token_to(
+            &env,
+            env.current_contract_address(),
+            token.clone(),
+            to.clone(),
+            amount,
+        )?;
+        Self::record_flow(&env, &token, 0, amount);
+        Self::update_token_stats(&env, &token, |stats| {
+            stats.pending = stats.pending.saturating_sub(amount)
+        });
+        events::claimable_claimed(&env, token, recipient, to, amount);
+        Ok(())
+    }
+
+    /// Pauses deposits and/or withdrawals for `token`, or for every token when
+    /// `token` is `None`. Only flags passed as `true` are changed.
+    pub fn pause(
+        env: Env,
+        token: Option<Address>,
+        deposits: bool,
+        withdrawals: bool,
+    ) -> Result<(), ContractError> {
+        let guardian = read_guardian(&env)?;
+        guardian.require_auth();
+
+        let mut state = read_pause_state(&env, token.clone());
+        state.deposits = state.deposits || deposits;
+        state.withdrawals = state.withdrawals || withdrawals;
+        write_pause_state(&env, token.clone(), &state);
+        events::paused(&env, token, state);
+        Ok(())
+    }
+
+    /// Lifts a pause set by the guardian. Only flags passed as `true` are cleared.
+    pub fn unpause(env: Env, token: Option<Address>, deposits: bool, withdrawals: bool) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let mut state = read_pause_state(&env, token.clone());
+        state.deposits = state.deposits && !deposits;
+        state.withdrawals = state.withdrawals && !withdrawals;
+        write_pause_state(&env, token.clone(), &state);
+        events::unpaused(&env, token, state);
+    }
+
+    pub fn get_pause_state(env: Env, token: Option<Address>) -> PauseState {
+        read_pause_state(&env, token)
+    }
+
+    pub fn is_deposit_paused(env: Env, token: Address) -> bool {
+        read_pause_state(&env, None).deposits || read_pause_state(&env, Some(token)).deposits
+    }
+
+    pub fn is_withdraw_paused(env: Env, token: Address) -> bool {
+        read_pause_state(&env, None).withdrawals || read_pause_state(&env, Some(token)).withdrawals
+    }
+
+    pub fn get_pending_withdrawal(env: Env, id: u64) -> Result<PendingWithdrawal, ContractError> {
+        read_pending_withdrawal(&env, id)
+    }
+
+    pub fn set_withdrawal_delay(env: Env, delay: u64) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        write_withdrawal_delay(&env, delay);
+        events::withdrawal_delay_updated(&env, delay);
+    }
+
+    pub fn get_withdrawal_delay(env: Env) -> u64 {
+        read_withdrawal_delay(&env)
+    }
+
+    /// Executes a queued withdrawal once it is unlocked, withdrawals are not
+    /// paused and the rate limit has capacity for it. Withdrawals other than
+    /// refunds also have to fit within the recipient limit again. Callable by
+    /// anyone.
+    pub fn claim_withdrawal(env: Env, id: u64) -> Result<(), ContractError> {
+        let withdrawal = read_pending_withdrawal(&env, id)?;
+        if Self::is_withdraw_paused(env.clone(), withdrawal.token.clone()) {
+            return Err(ContractError::Paused);
+        }
+        if env.ledger().timestamp() < withdrawal.unlock_time {
+            return Err(ContractError::WithdrawalLocked);
+        }
+        if !withdrawal.refund {
+            if !Self::within_recipient_limit(
+                &env,
+                &withdrawal.to,
+                &withdrawal.token,
+                withdrawal.amount,
+            ) {
+                return Err(ContractError::ExceedsWithdrawLimit);
+            }
+            Self::record_recipient_withdrawal(
+                &env,
+                &withdrawal.to,
+                &withdrawal.token,
+                withdrawal.amount,
+            );
+        }
+        remove_pending_withdrawal(&env, id);
+        remove_withdrawal_approvals(&env, id);
+
+        Self::execute_withdraw(
+            &env,
+            env.current_contract_address(),
+            withdrawal.token,
+            withdrawal.to,
+            withdrawal.amount,
+            withdrawal.refund,
+        )?;
+        events::withdrawal_claimed(&env, id);
+        Ok(())
+    }
+
+    /// Drops a queued withdrawal while it is still locked or its token is
+    /// paused. The funds stay in the contract.
+    pub fn cancel_withdrawal(env: Env, caller: Address, id: u64) -> Result<(), ContractError> {
+        Self::require_admin_or_guardian(&env, &caller)?;
+
+        let withdrawal = read_pending_withdrawal(&env, id)?;
+        if env.ledger().timestamp() >= withdrawal.unlock_time
+            && !Self::is_withdraw_paused(env.clone(), withdrawal.token.clone())
+        {
+            return Err(ContractError::WithdrawalUnlocked);
+        }
+        remove_pending_withdrawal(&env, id);
+        remove_withdrawal_approvals(&env, id);
+
+        events::withdrawal_cancelled(&env, id, caller);
+        Ok(())
+    }
+
+    /// Sets the guardians that co-sign withdrawals above the approval threshold
+    /// of their token, how many of them have to approve one and the delay after
+    /// which it unlocks without enough approvals, which can't be 0.
+    pub fn configure_approvals(
+        env: Env,
+        guardians: Vec<Address>,
+        threshold: u32,
+        max_delay: u64,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if threshold == 0 || threshold > guardians.len() || max_delay == 0 {
+            return Err(ContractError::InvalidApprovalConfig);
+        }
+        for (index, guardian) in guardians.iter().enumerate() {
+            if guardians.first_index_of(&guardian) != Some(index as u32) {
+                return Err(ContractError::InvalidApprovalConfig);
+            }
+        }
+        let config = ApprovalConfig {
+            guardians,
+            threshold,
+            max_delay,
+        };
+        set_approval_config(&env, &config);
+        events::approvals_configured(&env, config);
+        Ok(())
+    }
+
+    pub fn get_approval_config(env: Env) -> Option<ApprovalConfig> {
+        get_approval_config(&env)
+    }
+
+    /// Withdrawals of more than `threshold` of `token` have to be approved by
+    /// the guardians. A threshold of 0 removes it.
+    pub fn set_approval_threshold(
+        env: Env,
+        token: Address,
+        threshold: u128,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if threshold == 0 {
+            remove_approval_threshold(&env, &token);
+        } else if get_approval_config(&env).is_none() {
+            return Err(ContractError::ApprovalsNotConfigured);
+        } else {
+            write_approval_threshold(&env, &token, threshold);
+        }
+        events::approval_threshold_updated(&env, token, threshold);
+        Ok(())
+    }
+
+    pub fn get_approval_threshold(env: Env, token: Address) -> Option<u128> {
+        read_approval_threshold(&env, &token)
+    }
+
+    pub fn get_withdrawal_approvals(env: Env, id: u64) -> Result<Vec<Address>, ContractError> {
+        read_withdrawal_approvals(&env, id).ok_or(ContractError::WithdrawalNotAwaitingApproval)
+    }
+
+    /// Approves a withdrawal awaiting guardian approval. Once enough of the
+    /// current guardians approved it, it can be claimed right away. Approvals
+    /// of guardians removed since then don't count.
+    pub fn approve_withdrawal(env: Env, guardian: Address, id: u64) -> Result<(), ContractError> {
+        let config = Self::require_approval_guardian(&env, &guardian)?;
+
+        let mut withdrawal = read_pending_withdrawal(&env, id)?;
+        let mut approvals = read_withdrawal_approvals(&env, id)
+            .ok_or(ContractError::WithdrawalNotAwaitingApproval)?;
+        if approvals.contains(&guardian) {
+            return Err(ContractError::AlreadyApproved);
+        }
+        approvals.push_back(guardian.clone());
+        let approved = approvals
+            .iter()
+            .filter(|approval| config.guardians.contains(approval))
+            .count() as u32;
+        events::withdrawal_approved(&env, id, guardian, approved);
+
+        if approved < config.threshold {
+            write_withdrawal_approvals(&env, id, &approvals);
+            return Ok(());
+        }
+        remove_withdrawal_approvals(&env, id);
+        withdrawal.unlock_time = withdrawal.unlock_time.min(env.ledger().timestamp());
+        update_pending_withdrawal(&env, id, &withdrawal);
+        events::withdrawal_released(&env, id);
+        Ok(())
+    }
+
+    /// Drops a withdrawal awaiting guardian approval before it unlocks. The funds
+    /// stay in the contract.
+    pub fn reject_withdrawal(env: Env, guardian: Address, id: u64) -> Result<(), ContractError> {
+        Self::require_approval_guardian(&env, &guardian)?;
+
+        let withdrawal = read_pending_withdrawal(&env, id)?;
+        if read_withdrawal_approvals(&env, id).is_none() {
+            return Err(ContractError::WithdrawalNotAwaitingApproval);
+        }
+        if env.ledger().timestamp() >= withdrawal.unlock_time {
+            return Err(ContractError::WithdrawalUnlocked);
+        }
+        remove_pending_withdrawal(&env, id);
+        remove_withdrawal_approvals(&env, id);
+
+        events::withdrawal_rejected(&env, id, guardian);
+        Ok(())
+    }
+
+    fn require_approval_guardian(
+        env: &Env,
+        guardian: &Address,
+    ) -> Result<ApprovalConfig, ContractError> {
+        let config = get_approval_config(env).ok_or(ContractError::ApprovalsNotConfigured)?;
+        if !config.guardians.contains(guardian) {
+            return Err(ContractError::OnlyApprovalGuardian);
+        }
+        guardian.require_auth();
+        Ok(config)
+    }
+
+    fn require_admin_or_guardian(env: &Env, caller: &Address) -> Result<(), ContractError> {
+        if *caller != read_administrator(env) && Ok(caller.clone()) != read_guardian(env) {
+            return Err(ContractError::OnlyAdminOrGuardian);
+        }
+        caller.require_auth();
+        Ok(())
+    }
+
     pub fn configure(env: Env, config: ConfigData) {
         let admin = read_administrator(&env);
         admin.require_auth();
 
-        set_config(&env, config);
+        set_config(&env, config.clone());
+        events::configured(&env, config);
     }
 
     pub fn configure_rate_limit(
@@ -86,17 +480,640 @@ impl AssetManager {
 
         write_token_data(
             &env,
-            token_address,
+            token_address.clone(),
             TokenData {
                 period,
                 percentage,
                 last_update: env.ledger().timestamp(),
                 current_limit: 0,
+                max_withdraw: 0,
+                withdrawn: 0,
+                windows: Vec::new(&env),
             },
         );
+        events::rate_limit_configured(&env, token_address, period, percentage);
+        Ok(())
+    }
+
+    pub fn update_rate_limit(
+        env: Env,
+        token_address: Address,
+        period: u64,
+        percentage: u32,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+        if percentage > POINTS as u32 {
+            return Err(ContractError::PercentageShouldBeLessThanOrEqualToPOINTS);
+        }
+
+        // Carry over the reserve decayed under the old parameters, raised to the
+        // new minimum when the percentage goes up.
+        let balance = Self::get_token_balance(&env, token_address.clone());
+        let mut data = read_token_data(&env, token_address.clone())?;
+        if data.windows.iter().any(|w| w.period == period) {
+            return Err(ContractError::RateLimitWindowExists);
+        }
+        // The withdraw cap is released over this period.
+        if period == 0 && data.max_withdraw > 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let now = env.ledger().timestamp();
+        let reserve = Self::calculate_reserve(now, balance, &Self::primary_window(&data));
+        let min_reserve = balance * percentage as u128 / POINTS;
+        data.withdrawn = Self::calculate_withdrawn(now, &data);
+        data.current_limit = reserve.max(min_reserve) as u64;
+        data.last_update = now;
+        data.period = period;
+        data.percentage = percentage;
+        write_token_data(&env, token_address.clone(), data);
+
+        events::rate_limit_updated(&env, token_address, period, percentage);
+        Ok(())
+    }
+
+    pub fn remove_rate_limit(env: Env, token_address: Address) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+        read_token_data(&env, token_address.clone())?;
+
+        if let Some(group) = read_token_rate_limit_group(&env, &token_address) {
+            Self::leave_rate_limit_group(&env, &token_address, &group);
+            events::rate_limit_group_left(&env, token_address.clone(), group);
+        }
+        remove_token_data(&env, token_address.clone());
+        remove_token(&env, token_address.clone());
+
+        events::rate_limit_removed(&env, token_address);
+        Ok(())
+    }
+
+    /// Caps the amount of `token_address` that can be withdrawn per rolling
+    /// `period`, on top of the percentage reserve. A cap of 0 removes it. The
+    /// cap is released over the rate limit period, so it can't be set while
+    /// that period is 0.
+    pub fn set_withdraw_cap(
+        env: Env,
+        token_address: Address,
+        max_withdraw: u128,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let balance = Self::get_token_balance(&env, token_address.clone());
+        let mut data = read_token_data(&env, token_address.clone())?;
+        if data.period == 0 && max_withdraw > 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let now = env.ledger().timestamp();
+        data.current_limit =
+            Self::calculate_reserve(now, balance, &Self::primary_window(&data)) as u64;
+        data.withdrawn = Self::calculate_withdrawn(now, &data);
+        data.last_update = now;
+        data.max_withdraw = max_withdraw;
+        write_token_data(&env, token_address.clone(), data);
+
+        events::withdraw_cap_updated(&env, token_address, max_withdraw);
+        Ok(())
+    }
+
+    /// Adds a rate limit window for `token_address` on top of the one set by
+    /// `configure_rate_limit`, e.g. an hourly limit next to a daily one. A
+    /// withdrawal has to fit within every window.
+    pub fn add_rate_limit_window(
+        env: Env,
+        token_address: Address,
+        period: u64,
+        percentage: u32,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+        if percentage > POINTS as u32 {
+            return Err(ContractError::PercentageShouldBeLessThanOrEqualToPOINTS);
+        }
+        if period == 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let mut data = read_token_data(&env, token_address.clone())?;
+        if period == data.period || data.windows.iter().any(|w| w.period == period) {
+            return Err(ContractError::RateLimitWindowExists);
+        }
+        data.windows.push_back(RateLimitWindow {
+            period,
+            percentage,
+            last_update: env.ledger().timestamp(),
+            current_limit: 0,
+        });
+        write_token_data(&env, token_address.clone(), data);
+
+        events::rate_limit_window_added(&env, token_address, period, percentage);
+        Ok(())
+    }
+
+    pub fn remove_rate_limit_window(
+        env: Env,
+        token_address: Address,
+        period: u64,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let mut data = read_token_data(&env, token_address.clone())?;
+        let index = data
+            .windows
+            .iter()
+            .position(|w| w.period == period)
+            .ok_or(ContractError::RateLimitWindowNotFound)?;
+        data.windows.remove(index as u32);
+        write_token_data(&env, token_address.clone(), data);
+
+        events::rate_limit_window_removed(&env, token_address, period);
+        Ok(())
+    }
+
+    /// Returns the windows added with `add_rate_limit_window`.
+    pub fn get_rate_limit_windows(
+        env: Env,
+        token_address: Address,
+    ) -> Result<Vec<RateLimitWindow>, ContractError> {
+        Ok(read_token_data(&env, token_address)?.windows)
+    }
+
+    /// Returns the current reserve and remaining withdrawable amount of every
+    /// rate limit window of `token_address`, starting with the primary one.
+    pub fn get_window_capacities(
+        env: Env,
+        token_address: Address,
+    ) -> Result<Vec<WindowCapacity>, ContractError> {
+        let balance = Self::get_token_balance(&env, token_address.clone());
+        let data = read_token_data(&env, token_address)?;
+        let now = env.ledger().timestamp();
+        let mut capacities = Vec::new(&env);
+        for window in Self::all_windows(&env, &data).iter() {
+            let reserve = Self::calculate_reserve(now, balance, &window);
+            capacities.push_back(WindowCapacity {
+                period: window.period,
+                percentage: window.percentage,
+                reserve,
+                available: balance.saturating_sub(reserve),
+            });
+        }
+        Ok(capacities)
+    }
+
+    /// Creates or updates the rate limit group `group`. Its reserve is computed
+    /// over the combined balance of the tokens added with `add_to_rate_limit_group`.
+    pub fn configure_rate_limit_group(
+        env: Env,
+        group: Symbol,
+        period: u64,
+        percentage: u32,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+        if percentage > POINTS as u32 {
+            return Err(ContractError::PercentageShouldBeLessThanOrEqualToPOINTS);
+        }
+
+        let now = env.ledger().timestamp();
+        let data = match read_rate_limit_group(&env, &group) {
+            Some(mut data) => {
+                // Carry over the reserve like `update_rate_limit` does.
+                let balance = Self::group_balance(&env, &data.tokens);
+                let reserve = Self::calculate_reserve(now, balance, &data.limit);
+                let min_reserve = balance * percentage as u128 / POINTS;
+                data.limit = RateLimitWindow {
+                    period,
+                    percentage,
+                    last_update: now,
+                    current_limit: reserve.max(min_reserve) as u64,
+                };
+                data
+            }
+            None => RateLimitGroup {
+                tokens: Vec::new(&env),
+                limit: RateLimitWindow {
+                    period,
+                    percentage,
+                    last_update: now,
+                    current_limit: 0,
+                },
+            },
+        };
+        write_rate_limit_group(&env, &group, &data);
+
+        events::rate_limit_group_configured(&env, group, period, percentage);
+        Ok(())
+    }
+
+    pub fn remove_rate_limit_group(env: Env, group: Symbol) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let data = read_rate_limit_group(&env, &group)
+            .ok_or(ContractError::RateLimitGroupNotFound)?;
+        for token in data.tokens.iter() {
+            remove_token_rate_limit_group(&env, &token);
+        }
+        remove_rate_limit_group(&env, &group);
+
+        events::rate_limit_group_removed(&env, group);
+        Ok(())
+    }
+
+    /// Adds `token` to `group`. The token needs its own rate limit and can be
+    /// a member of a single group.
+    pub fn add_to_rate_limit_group(
+        env: Env,
+        group: Symbol,
+        token: Address,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let mut data = read_rate_limit_group(&env, &group)
+            .ok_or(ContractError::RateLimitGroupNotFound)?;
+        read_token_data(&env, token.clone())?;
+        if read_token_rate_limit_group(&env, &token).is_some() {
+            return Err(ContractError::TokenAlreadyInRateLimitGroup);
+        }
+        data.tokens.push_back(token.clone());
+        write_rate_limit_group(&env, &group, &data);
+        write_token_rate_limit_group(&env, &token, &group);
+
+        events::rate_limit_group_joined(&env, token, group);
+        Ok(())
+    }
+
+    pub fn remove_from_rate_limit_group(env: Env, token: Address) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let group = read_token_rate_limit_group(&env, &token)
+            .ok_or(ContractError::TokenNotInRateLimitGroup)?;
+        Self::leave_rate_limit_group(&env, &token, &group);
+
+        events::rate_limit_group_left(&env, token, group);
+        Ok(())
+    }
+
+    pub fn get_rate_limit_group(env: Env, group: Symbol) -> Option<RateLimitGroup> {
+        read_rate_limit_group(&env, &group)
+    }
+
+    pub fn get_token_rate_limit_group(env: Env, token: Address) -> Option<Symbol> {
+        read_token_rate_limit_group(&env, &token)
+    }
+
+    /// Returns the current reserve of `group` and the combined amount of its
+    /// tokens that can still be withdrawn.
+    pub fn get_rate_limit_group_capacity(
+        env: Env,
+        group: Symbol,
+    ) -> Result<WindowCapacity, ContractError> {
+        let data = read_rate_limit_group(&env, &group)
+            .ok_or(ContractError::RateLimitGroupNotFound)?;
+        let balance = Self::group_balance(&env, &data.tokens);
+        let reserve = Self::calculate_reserve(env.ledger().timestamp(), balance, &data.limit);
+        Ok(WindowCapacity {
+            period: data.limit.period,
+            percentage: data.limit.percentage,
+            reserve,
+            available: balance.saturating_sub(reserve),
+        })
+    }
+
+    fn leave_rate_limit_group(env: &Env, token: &Address, group: &Symbol) {
+        if let Some(mut data) = read_rate_limit_group(env, group) {
+            if let Some(index) = data.tokens.first_index_of(token) {
+                data.tokens.remove(index);
+                write_rate_limit_group(env, group, &data);
+            }
+        }
+        remove_token_rate_limit_group(env, token);
+    }
+
+    fn token_rate_limit_group(env: &Env, token: &Address) -> Option<(Symbol, RateLimitGroup)> {
+        let group = read_token_rate_limit_group(env, token)?;
+        let data = read_rate_limit_group(env, &group)?;
+        Some((group, data))
+    }
+
+    fn group_balance(env: &Env, tokens: &Vec<Address>) -> u128 {
+        tokens
+            .iter()
+            .map(|token| Self::get_token_balance(env, token))
+            .sum()
+    }
+
+    /// Limits how much of `token` a single recipient can withdraw per rolling
+    /// `period` and how often. Withdrawals over the limit are queued like those
+    /// over the rate limit and can only be claimed once they fit within it,
+    /// refunds of reverted deposits are not limited. A limit without
+    /// `max_amount` and `cooldown` removes it.
+    pub fn set_recipient_limit(
+        env: Env,
+        token: Address,
+        limit: RecipientLimit,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if limit.max_amount > 0 && limit.period == 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if limit.max_amount == 0 && limit.cooldown == 0 {
+            remove_recipient_limit(&env, &token);
+        } else {
+            write_recipient_limit(&env, &token, &limit);
+        }
+        events::recipient_limit_updated(&env, token, limit);
+        Ok(())
+    }
+
+    pub fn get_recipient_limit(env: Env, token: Address) -> Option<RecipientLimit> {
+        read_recipient_limit(&env, &token)
+    }
+
+    /// Exempts `recipient`, e.g. a known protocol address, from recipient limits.
+    pub fn set_recipient_exemption(env: Env, recipient: Address, exempt: bool) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let mut recipients = read_exempt_recipients(&env);
+        match recipients.first_index_of(&recipient) {
+            Some(index) if !exempt => {
+                recipients.remove(index);
+            }
+            None if exempt => recipients.push_back(recipient.clone()),
+            _ => {}
+        }
+        write_exempt_recipients(&env, &recipients);
+        events::recipient_exemption_updated(&env, recipient, exempt);
+    }
+
+    pub fn get_exempt_recipients(env: Env) -> Vec<Address> {
+        read_exempt_recipients(&env)
+    }
+
+    /// Returns the amount of `token` that `recipient` can currently withdraw,
+    /// or `None` if its withdrawals are not limited.
+    pub fn get_recipient_available(env: Env, recipient: Address, token: Address) -> Option<u128> {
+        let limit = read_recipient_limit(&env, &token)?;
+        if limit.max_amount == 0 || read_exempt_recipients(&env).contains(&recipient) {
+            return None;
+        }
+        let withdrawn = match read_recipient_usage(&env, &recipient, &token) {
+            Some(usage) => Self::calculate_recipient_withdrawn(&env, &limit, &usage),
+            None => 0,
+        };
+        Some(limit.max_amount.saturating_sub(withdrawn))
+    }
+
+    fn calculate_recipient_withdrawn(
+        env: &Env,
+        limit: &RecipientLimit,
+        usage: &RecipientUsage,
+    ) -> u128 {
+        if limit.period == 0 {
+            return 0;
+        }
+        let time_diff = env.ledger().timestamp() - usage.last_withdrawal;
+        let released = limit.max_amount * time_diff as u128 / limit.period as u128;
+        usage.withdrawn.saturating_sub(released)
+    }
+
+    /// Returns whether `recipient` can be paid `amount` of `token` now without
+    /// exceeding its recipient limit or cooldown.
+    fn within_recipient_limit(
+        env: &Env,
+        recipient: &Address,
+        token: &Address,
+        amount: u128,
+    ) -> bool {
+        let limit = match read_recipient_limit(env, token) {
+            Some(limit) => limit,
+            None => return true,
+        };
+        if read_exempt_recipients(env).contains(recipient) {
+            return true;
+        }
+        let usage = match read_recipient_usage(env, recipient, token) {
+            Some(usage) => usage,
+            None => return limit.max_amount == 0 || amount <= limit.max_amount,
+        };
+        if env.ledger().timestamp() < usage.last_withdrawal + limit.cooldown {
+            return false;
+        }
+        let withdrawn = Self::calculate_recipient_withdrawn(env, &limit, &usage);
+        limit.max_amount == 0 || withdrawn + amount <= limit.max_amount
+    }
+
+    fn record_recipient_withdrawal(env: &Env, recipient: &Address, token: &Address, amount: u128) {
+        let limit = match read_recipient_limit(env, token) {
+            Some(limit) => limit,
+            None => return,
+        };
+        if read_exempt_recipients(env).contains(recipient) {
+            return;
+        }
+        let withdrawn = match read_recipient_usage(env, recipient, token) {
+            Some(usage) => Self::calculate_recipient_withdrawn(env, &limit, &usage),
+            None => 0,
+        };
+        let usage = RecipientUsage {
+            withdrawn: withdrawn + amount,
+            last_withdrawal: env.ledger().timestamp(),
+        };
+        let live_for = limit.period.max(limit.cooldown);
+        write_recipient_usage(env, recipient, token, &usage, live_for);
+    }
+
+    /// Caps the amount of `token` that can be deposited per rolling `period`,
+    /// independently of the withdrawal rate limit. The capacity refills linearly
+    /// over the period.
+    pub fn set_deposit_limit(
+        env: Env,
+        token: Address,
+        period: u64,
+        max_deposit: u128,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if period == 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let deposited = match read_deposit_limit(&env, &token) {
+            Some(limit) => Self::calculate_deposited(&env, &limit),
+            None => 0,
+        };
+        let limit = DepositLimit {
+            period,
+            max_deposit,
+            deposited,
+            last_update: env.ledger().timestamp(),
+        };
+        write_deposit_limit(&env, &token, &limit);
+        events::deposit_limit_updated(&env, token, period, max_deposit);
+        Ok(())
+    }
+
+    pub fn remove_deposit_limit(env: Env, token: Address) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if read_deposit_limit(&env, &token).is_none() {
+            return Err(ContractError::DepositLimitNotSet);
+        }
+        remove_deposit_limit(&env, &token);
+        events::deposit_limit_removed(&env, token);
+        Ok(())
+    }
+
+    pub fn get_deposit_limit(env: Env, token: Address) -> Option<DepositLimit> {
+        read_deposit_limit(&env, &token)
+    }
+
+    /// Returns the amount of `token` that can currently be deposited, or `None`
+    /// if deposits of `token` are not limited.
+    pub fn get_available_deposit(env: Env, token: Address) -> Option<u128> {
+        let limit = read_deposit_limit(&env, &token)?;
+        Some(
+            limit
+                .max_deposit
+                .saturating_sub(Self::calculate_deposited(&env, &limit)),
+        )
+    }
+
+    fn calculate_deposited(env: &Env, limit: &DepositLimit) -> u128 {
+        let time_diff = env.ledger().timestamp() - limit.last_update;
+        let released = limit.max_deposit * time_diff as u128 / limit.period as u128;
+        limit.deposited.saturating_sub(released)
+    }
+
+    fn record_deposit(env: &Env, token: &Address, amount: u128) -> Result<(), ContractError> {
+        let mut limit = match read_deposit_limit(env, token) {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let deposited = Self::calculate_deposited(env, &limit) + amount;
+        if deposited > limit.max_deposit {
+            return Err(ContractError::ExceedsDepositLimit);
+        }
+        limit.deposited = deposited;
+        limit.last_update = env.ledger().timestamp();
+        write_deposit_limit(env, token, &limit);
+        Ok(())
+    }
+
+    pub fn set_deposit_fee(
+        env: Env,
+        token: Address,
+        fee: DepositFee,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if let FeeType::BasisPoints(basis_points) = fee.fee_type {
+            if basis_points as u128 > POINTS {
+                return Err(ContractError::PercentageShouldBeLessThanOrEqualToPOINTS);
+            }
+        }
+        write_deposit_fee(&env, token.clone(), &fee);
+        events::deposit_fee_updated(&env, token, fee);
+        Ok(())
+    }
+
+    pub fn remove_deposit_fee(env: Env, token: Address) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if read_deposit_fee(&env, token.clone()).is_none() {
+            return Err(ContractError::DepositFeeNotSet);
+        }
+        remove_deposit_fee(&env, token.clone());
+        events::deposit_fee_removed(&env, token);
+        Ok(())
+    }
+
+    pub fn get_deposit_fee(env: Env, token: Address) -> Option<DepositFee> {
+        read_deposit_fee(&env, token)
+    }
+
+    /// Sets the decimals `token` is accounted in on the ICON hub. Amounts in
+    /// xCall messages are scaled between these and the decimals of the token.
+    pub fn set_token_decimals(
+        env: Env,
+        token: Address,
+        hub_decimals: u32,
+    ) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let decimals = TokenDecimals {
+            local: token::Client::new(&env, &token).decimals(),
+            hub: hub_decimals,
+        };
+        if scale_amount(1, decimals.local, decimals.hub).is_none()
+            || scale_amount(1, decimals.hub, decimals.local).is_none()
+        {
+            return Err(ContractError::InvalidDecimals);
+        }
+        write_token_decimals(&env, &token, &decimals);
+        events::token_decimals_updated(&env, token, decimals);
+        Ok(())
+    }
+
+    pub fn remove_token_decimals(env: Env, token: Address) -> Result<(), ContractError> {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if read_token_decimals(&env, &token).is_none() {
+            return Err(ContractError::TokenDecimalsNotSet);
+        }
+        remove_token_decimals(&env, &token);
+        events::token_decimals_removed(&env, token);
         Ok(())
     }
 
+    pub fn get_token_decimals(env: Env, token: Address) -> Option<TokenDecimals> {
+        read_token_decimals(&env, &token)
+    }
+
+    /// Returns the fee taken from a deposit of `amount`, the rest is sent to ICON.
+    pub fn calculate_deposit_fee(
+        env: Env,
+        token: Address,
+        amount: u128,
+    ) -> Result<u128, ContractError> {
+        let fee = match read_deposit_fee(&env, token) {
+            Some(fee) => fee,
+            None => return Ok(0),
+        };
+        let fee_amount = match fee.fee_type {
+            FeeType::BasisPoints(basis_points) => amount * basis_points as u128 / POINTS,
+            FeeType::Fixed(fixed) => fixed,
+        };
+        let fee_amount = fee_amount.max(fee.minimum);
+        if fee_amount >= amount {
+            return Err(ContractError::AmountIsLessThanMinimumAmount);
+        }
+        Ok(fee_amount)
+    }
+
+    /// Returns the xCall fee, in the native token, that a deposit to the ICON asset
+    /// manager currently costs. `rollback` should be `true` for regular deposits,
+    /// which are sent with a rollback message.
+    pub fn quote_deposit_fee(env: Env, rollback: bool) -> Result<u128, ContractError> {
+        let config = get_config(&env);
+        let nid = get_network_id(&config.icon_asset_manager)
+            .ok_or(ContractError::InvalidNetworkAddress)?;
+        let (sources, _) = Self::xcall_manager(&env, &config.xcall_manager).get_protocols();
+        Ok(Self::xcall_client(&env, &config.xcall).get_fee(&nid, &rollback, &Some(sources)))
+    }
+
     pub fn get_rate_limit(env: Env, token_address: Address) -> Result<(u64, u32, u64, u64), ContractError> {
         let data: TokenData = read_token_data(&env, token_address)?;
         
@@ -108,9 +1125,22 @@ impl AssetManager {
         ))
     }
 
+    /// Returns the withdraw cap of `token_address` and the amount withdrawn
+    /// against it that hasn't been released yet.
+    pub fn get_withdraw_cap(
+        env: Env,
+        token_address: Address,
+    ) -> Result<(u128, u128), ContractError> {
+        let data: TokenData = read_token_data(&env, token_address)?;
+        let withdrawn = Self::calculate_withdrawn(env.ledger().timestamp(), &data);
+        Ok((data.max_withdraw, withdrawn))
+    }
+
     pub fn set_xcall_network_address(env: Env) {
         let xcall = get_config(&env).xcall;
-        set_xcall_network_address(&env, Self::xcall_client(&env, &xcall).get_network_address());
+        let network_address = Self::xcall_client(&env, &xcall).get_network_address();
+        set_xcall_network_address(&env, network_address.clone());
+        events::xcall_network_address_updated(&env, network_address);
     }
 
     pub fn reset_limit(env: Env, token: Address) -> Result<bool, ContractError> {
@@ -119,7 +1149,15 @@ impl AssetManager {
         let balance = Self::get_token_balance(&env, token.clone());
         let mut data: TokenData = read_token_data(&env, token.clone())?;
         data.current_limit = (balance * data.percentage as u128 / POINTS) as u64;
-        write_token_data(&env, token, data);
+        data.withdrawn = 0;
+        for i in 0..data.windows.len() {
+            let mut window = data.windows.get_unchecked(i);
+            window.current_limit = (balance * window.percentage as u128 / POINTS) as u64;
+            data.windows.set(i, window);
+        }
+        let current_limit = data.current_limit;
+        write_token_data(&env, token.clone(), data);
+        events::limit_reset(&env, token, current_limit);
         Ok(true)
     }
 
@@ -128,41 +1166,230 @@ impl AssetManager {
         return Ok(Self::calculate_limit(&env, balance, token)?);
     }
 
+    /// Predicts the checks a `WithdrawTo` of `amount` would go through right
+    /// now, without changing any state. The per-recipient limits are only
+    /// checked when a `recipient` is given.
+    pub fn simulate_withdraw(
+        env: Env,
+        token: Address,
+        amount: u128,
+        recipient: Option<Address>,
+    ) -> Result<WithdrawSimulation, ContractError> {
+        let balance = Self::get_token_balance(&env, token.clone());
+        let data = read_token_data(&env, token.clone())?;
+        let now = env.ledger().timestamp();
+        let reserve = Self::limit_at(&env, &token, balance, &data, now);
+        let paused = Self::is_withdraw_paused(env.clone(), token.clone());
+        let requires_approval = Self::approval_config_for(&env, &token, amount).is_some();
+        let recipient_blocked = match recipient {
+            Some(recipient) => !Self::within_recipient_limit(&env, &recipient, &token, amount),
+            None => false,
+        };
+        let within_rate_limit = balance >= amount && balance - amount >= reserve;
+        let withdrawable_at = if paused || requires_approval || recipient_blocked {
+            None
+        } else if within_rate_limit {
+            Some(now)
+        } else {
+            Self::withdrawable_at(&env, &token, balance, &data, amount, now)
+        };
+        Ok(WithdrawSimulation {
+            allowed: within_rate_limit && !paused && !requires_approval && !recipient_blocked,
+            reserve,
+            max_withdrawable: balance.saturating_sub(reserve),
+            withdrawable_at,
+            paused,
+            requires_approval,
+            recipient_blocked,
+        })
+    }
+
+    /// Returns the earliest time at which `amount` fits within every window, the
+    /// withdraw cap and the group limit, or `None` if it never does at the
+    /// current balances.
+    fn withdrawable_at(
+        env: &Env,
+        token: &Address,
+        balance: u128,
+        data: &TokenData,
+        amount: u128,
+        now: u64,
+    ) -> Option<u64> {
+        if amount > balance {
+            return None;
+        }
+        let target = balance - amount;
+        let mut at = now;
+        if data.period != 0 || !data.windows.is_empty() {
+            for window in Self::all_windows(env, data).iter() {
+                at = at.max(Self::window_release_time(balance, &window, target, now)?);
+            }
+            if data.max_withdraw > 0 {
+                at = at.max(Self::cap_release_time(data, amount, now)?);
+            }
+        }
+        if let Some((_, group)) = Self::token_rate_limit_group(env, token) {
+            let combined = Self::group_balance(env, &group.tokens);
+            let group_target = combined - amount;
+            at = at.max(Self::window_release_time(combined, &group.limit, group_target, now)?);
+        }
+
+        // Confirm against the real check, which rounds per window.
+        if Self::limit_at(env, token, balance, data, at) > target {
+            return None;
+        }
+        Some(at)
+    }
+
+    fn window_release_time(
+        balance: u128,
+        window: &RateLimitWindow,
+        target: u128,
+        now: u64,
+    ) -> Option<u64> {
+        if window.period == 0 || Self::calculate_reserve(now, balance, window) <= target {
+            return Some(now);
+        }
+        let min_reserve = balance * window.percentage as u128 / POINTS;
+        let max_withdraw = balance - min_reserve;
+        if min_reserve > target || max_withdraw == 0 {
+            return None;
+        }
+        let needed = window.current_limit as u128 - target;
+        let time_diff = (needed * window.period as u128).div_ceil(max_withdraw);
+        window.last_update.checked_add(u64::try_from(time_diff).ok()?)
+    }
+
+    fn cap_release_time(data: &TokenData, amount: u128, now: u64) -> Option<u64> {
+        if amount > data.max_withdraw {
+            return None;
+        }
+        if Self::calculate_withdrawn(now, data) + amount <= data.max_withdraw {
+            return Some(now);
+        }
+        let needed = data.withdrawn - (data.max_withdraw - amount);
+        let time_diff = (needed * data.period as u128).div_ceil(data.max_withdraw);
+        data.last_update.checked_add(u64::try_from(time_diff).ok()?)
+    }
+
     fn get_token_balance(env: &Env, token: Address) -> u128 {
         let token_client = token::Client::new(env, &token);
         return token_client.balance(&env.current_contract_address()) as u128;
     }
 
-    fn verify_withdraw(env: Env, token: Address, amount: u128) -> Result<bool, ContractError> {
-        let balance = Self::get_token_balance(&env, token.clone());
-        let limit = Self::calculate_limit(&env, balance, token.clone())?;
-        if balance - amount < limit {
-            return Err(ContractError::ExceedsWithdrawLimit);
-        };
-        let mut data: TokenData = read_token_data(&env, token.clone())?;
-        data.current_limit = limit as u64;
-        data.last_update = env.ledger().timestamp();
-        write_token_data(&env, token, data);
-        Ok(true)
+    fn can_withdraw(env: &Env, token: Address, amount: u128) -> Result<bool, ContractError> {
+        let balance = Self::get_token_balance(env, token.clone());
+        let limit = Self::calculate_limit(env, balance, token)?;
+        Ok(balance >= amount && balance - amount >= limit)
+    }
+
+    fn verify_withdraw(env: Env, token: Address, amount: u128) -> Result<bool, ContractError> {
+        if !Self::can_withdraw(&env, token.clone(), amount)? {
+            return Err(ContractError::ExceedsWithdrawLimit);
+        }
+        let balance = Self::get_token_balance(&env, token.clone());
+        let mut data: TokenData = read_token_data(&env, token.clone())?;
+        let now = env.ledger().timestamp();
+        data.current_limit =
+            Self::calculate_reserve(now, balance, &Self::primary_window(&data)) as u64;
+        if data.max_withdraw > 0 {
+            data.withdrawn = Self::calculate_withdrawn(now, &data) + amount;
+        }
+        data.last_update = now;
+        for i in 0..data.windows.len() {
+            let mut window = data.windows.get_unchecked(i);
+            window.current_limit = Self::calculate_reserve(now, balance, &window) as u64;
+            window.last_update = now;
+            data.windows.set(i, window);
+        }
+        write_token_data(&env, token.clone(), data);
+
+        if let Some((group, mut data)) = Self::token_rate_limit_group(&env, &token) {
+            let combined = Self::group_balance(&env, &data.tokens);
+            data.limit.current_limit = Self::calculate_reserve(now, combined, &data.limit) as u64;
+            data.limit.last_update = now;
+            write_rate_limit_group(&env, &group, &data);
+        }
+        Ok(true)
+    }
+
+    pub fn calculate_limit(
+        env: &Env,
+        balance: u128,
+        token: Address,
+    ) -> Result<u128, ContractError> {
+        let data: TokenData = read_token_data(&env, token.clone())?;
+        Ok(Self::limit_at(env, &token, balance, &data, env.ledger().timestamp()))
+    }
+
+    /// Returns the amount of `token` that can't be withdrawn at `now`, for
+    /// balances that don't change until then.
+    fn limit_at(env: &Env, token: &Address, balance: u128, data: &TokenData, now: u64) -> u128 {
+        Self::token_limit_at(env, balance, data, now)
+            .max(Self::group_limit_at(env, token, balance, now))
+    }
+
+    /// Expresses the reserve of the group of `token` as a limit on `token`,
+    /// which can be withdrawn as long as the group stays above its reserve.
+    fn group_limit_at(env: &Env, token: &Address, balance: u128, now: u64) -> u128 {
+        match Self::token_rate_limit_group(env, token) {
+            Some((_, group)) => {
+                let combined = Self::group_balance(env, &group.tokens);
+                let reserve = Self::calculate_reserve(now, combined, &group.limit);
+                balance.saturating_sub(combined.saturating_sub(reserve))
+            }
+            None => 0,
+        }
+    }
+
+    fn token_limit_at(env: &Env, balance: u128, data: &TokenData, now: u64) -> u128 {
+        if data.period == 0 && data.windows.is_empty() {
+            return 0;
+        }
+
+        // The strictest window decides how much has to stay in the contract.
+        let reserve = Self::all_windows(env, data)
+            .iter()
+            .map(|window| Self::calculate_reserve(now, balance, &window))
+            .max()
+            .unwrap_or(0);
+        if data.max_withdraw == 0 {
+            return reserve;
+        }
+
+        let remaining = data
+            .max_withdraw
+            .saturating_sub(Self::calculate_withdrawn(now, data));
+        reserve.max(balance.saturating_sub(remaining))
+    }
+
+    fn primary_window(data: &TokenData) -> RateLimitWindow {
+        RateLimitWindow {
+            period: data.period,
+            percentage: data.percentage,
+            last_update: data.last_update,
+            current_limit: data.current_limit,
+        }
+    }
+
+    fn all_windows(env: &Env, data: &TokenData) -> Vec<RateLimitWindow> {
+        let mut windows = Vec::from_array(env, [Self::primary_window(data)]);
+        windows.append(&data.windows);
+        windows
     }
 
-    pub fn calculate_limit(
-        env: &Env,
-        balance: u128,
-        token: Address,
-    ) -> Result<u128, ContractError> {
-        let data: TokenData = read_token_data(&env, token)?;
+    fn calculate_reserve(now: u64, balance: u128, data: &RateLimitWindow) -> u128 {
         let period: u128 = data.period as u128;
         let percentage: u128 = data.percentage as u128;
         if period == 0 {
-            return Ok(0);
+            return 0;
         }
 
         let min_reserve = (balance * percentage) / POINTS;
 
         let max_withdraw = balance - min_reserve;
         let last_update: u64 = data.last_update;
-        let time_diff = &env.ledger().timestamp() - last_update;
+        let time_diff = now - last_update;
 
         let allowed_withdrawal = (max_withdraw * time_diff as u128) / period;
         let mut reserve: u128 = data.current_limit as u128;
@@ -171,12 +1398,21 @@ impl AssetManager {
             reserve = reserve - allowed_withdrawal;
         }
 
-        let reserve = if reserve > min_reserve {
+        if reserve > min_reserve {
             reserve
         } else {
             min_reserve
-        };
-        Ok(reserve)
+        }
+    }
+
+    fn calculate_withdrawn(now: u64, data: &TokenData) -> u128 {
+        if data.period == 0 || data.max_withdraw == 0 {
+            return 0;
+        }
+
+        let time_diff = now - data.last_update;
+        let released = data.max_withdraw * time_diff as u128 / data.period as u128;
+        data.withdrawn.saturating_sub(released)
     }
 
     pub fn deposit(
@@ -196,6 +1432,7 @@ impl AssetManager {
 
         Ok(Self::send_deposit_message(
             e,
+            None,
             from,
             token,
             amount,
@@ -204,21 +1441,138 @@ impl AssetManager {
         )?)
     }
 
+    /// Deposits `amount` of the native token, `ConfigData.native_address`. Fails with
+    /// `InsufficientNativeBalance` unless `from` can pay both the deposit and the
+    /// xCall fee without dipping into its minimum reserve.
+    pub fn deposit_native(
+        e: Env,
+        from: Address,
+        amount: u128,
+        to: Option<String>,
+        data: Option<Bytes>,
+    ) -> Result<(), ContractError> {
+        let native_address = get_config(&e).native_address;
+        Self::deposit(e, from, native_address, amount, to, data)
+    }
+
+    /// Deposits `amount` of `token` on behalf of `from`, spending an allowance that
+    /// `from` has approved for `spender`. The spender pays the xCall fee, and a
+    /// revert refunds `from`.
+    pub fn deposit_from(
+        e: Env,
+        spender: Address,
+        from: Address,
+        token: Address,
+        amount: u128,
+        to: Option<String>,
+        data: Option<Bytes>,
+    ) -> Result<(), ContractError> {
+        if amount == 0 {
+            return Err(ContractError::AmountIsLessThanMinimumAmount);
+        }
+
+        let deposit_to = to.unwrap_or(String::from_str(&e, ""));
+        let deposit_data = data.unwrap_or(Bytes::from_array(&e, &[0u8; 32]));
+
+        Self::send_deposit_message(
+            e,
+            Some(spender),
+            from,
+            token,
+            amount,
+            deposit_to,
+            deposit_data,
+        )
+    }
+
+    /// Deposits several tokens to the same recipient with a single xCall message.
+    /// Each leg is charged its own deposit fee, and a revert refunds every leg.
+    pub fn deposit_batch(
+        e: Env,
+        from: Address,
+        deposits: Vec<(Address, u128)>,
+        to: Option<String>,
+        data: Option<Bytes>,
+    ) -> Result<(), ContractError> {
+        from.require_auth();
+        if deposits.is_empty() {
+            return Err(ContractError::EmptyBatch);
+        }
+
+        let deposit_to = to.unwrap_or(String::from_str(&e, ""));
+        let deposit_data = data.unwrap_or(Bytes::from_array(&e, &[0u8; 32]));
+        let config = get_config(&e);
+
+        let mut tokens: Vec<Address> = Vec::new(&e);
+        let mut amounts: Vec<u128> = Vec::new(&e);
+        let mut hub_amounts: Vec<u128> = Vec::new(&e);
+        let mut fees: Vec<u128> = Vec::new(&e);
+        for (token, amount) in deposits.iter() {
+            if amount == 0 {
+                return Err(ContractError::AmountIsLessThanMinimumAmount);
+            }
+            let (net_amount, fee, hub_amount) =
+                Self::collect_deposit(&e, &config, None, from.clone(), token.clone(), amount)?;
+            tokens.push_back(token);
+            amounts.push_back(net_amount);
+            hub_amounts.push_back(hub_amount);
+            fees.push_back(fee);
+        }
+
+        let mut token_addresses: Vec<String> = Vec::new(&e);
+        for token in tokens.iter() {
+            token_addresses.push_back(token.to_string());
+        }
+        let xcall_message = DepositBatch::new(
+            token_addresses,
+            from.to_string(),
+            deposit_to.clone(),
+            hub_amounts,
+            deposit_data,
+        );
+        let rollback = DepositBatchRevert::new(tokens.clone(), from.clone(), amounts.clone());
+        let sn = Self::send_deposit_call(
+            &e,
+            &config,
+            &from,
+            xcall_message.encode(&e, String::from_str(&e, DEPOSIT_BATCH_NAME)),
+            rollback.encode(&e, String::from_str(&e, DEPOSIT_BATCH_REVERT_NAME)),
+        )?;
+
+        for (i, token) in tokens.iter().enumerate() {
+            events::deposit(
+                &e,
+                token,
+                DepositEvent {
+                    from: from.clone(),
+                    to: deposit_to.clone(),
+                    amount: amounts.get(i as u32).unwrap(),
+                    fee: fees.get(i as u32).unwrap(),
+                    sn,
+                },
+            );
+        }
+        Ok(())
+    }
+
     fn send_deposit_message(
         e: Env,
+        spender: Option<Address>,
         from: Address,
         token: Address,
         amount: u128,
         to: String,
         data: Bytes,
     ) -> Result<(), ContractError> {
-        from.require_auth();
-        let current_address = e.current_contract_address();
-        Self::transfer_token_to(
+        let payer = spender.clone().unwrap_or(from.clone());
+        payer.require_auth();
+        let config = get_config(&e);
+        let (amount, fee, hub_amount) = Self::collect_deposit(
             &e,
+            &config,
+            spender.as_ref(),
             from.clone(),
             token.clone(),
-            current_address.clone(),
             amount,
         )?;
 
@@ -226,33 +1580,150 @@ impl AssetManager {
             token.to_string(),
             from.to_string(),
             to.clone(),
-            amount,
+            hub_amount,
             data,
         );
 
-        let rollback: DepositRevert = DepositRevert::new(token, from.clone(), amount);
-        let config = get_config(&e);
+        // Only the net amount is held by the contract, the fee is not refunded
+        // if the deposit is reverted.
+        let rollback: DepositRevert = DepositRevert::new(token.clone(), from.clone(), amount);
         let rollback_bytes = rollback.encode(&e, String::from_str(&e, DEPOSIT_REVERT_NAME));
         let message_bytes = xcall_message.encode(&e, String::from_str(&e, DEPOSIT_NAME));
-        let (sources, destinations) =
-            Self::xcall_manager(&e, &config.xcall_manager).get_protocols();
+        let sn = Self::send_deposit_call(&e, &config, &payer, message_bytes, rollback_bytes)?;
+        events::deposit(
+            &e,
+            token,
+            DepositEvent {
+                from,
+                to,
+                amount,
+                fee,
+                sn,
+            },
+        );
+        Ok(())
+    }
+
+    /// Moves a deposit into the contract, sending the deposit fee to the fee collector.
+    /// When a `spender` is given the tokens are moved out of its allowance from `from`.
+    /// Dust that can't be represented with the hub decimals is left with `from`.
+    /// Returns the net amount held by the contract, the fee charged and the net
+    /// amount scaled to the hub decimals.
+    fn collect_deposit(
+        e: &Env,
+        config: &ConfigData,
+        spender: Option<&Address>,
+        from: Address,
+        token: Address,
+        amount: u128,
+    ) -> Result<(u128, u128, u128), ContractError> {
+        if Self::is_deposit_paused(e.clone(), token.clone()) {
+            return Err(ContractError::Paused);
+        }
+        let fee = Self::calculate_deposit_fee(e.clone(), token.clone(), amount)?;
+        let (hub_amount, dust) = Self::to_hub_amount(e, &token, amount - fee)?;
+        let amount = amount - fee - dust;
+        if amount == 0 {
+            return Err(ContractError::AmountIsLessThanMinimumAmount);
+        }
+        Self::record_deposit(e, &token, amount)?;
+        Self::record_flow(e, &token, amount, 0);
+        Self::update_token_stats(e, &token, |stats| {
+            stats.deposited += amount;
+            stats.deposit_count += 1;
+            stats.last_deposit = e.ledger().timestamp();
+        });
+        let current_address = e.current_contract_address();
+        match spender {
+            Some(spender) => {
+                Self::transfer_token_from(e, spender, &from, &token, &current_address, amount)?;
+                if fee > 0 {
+                    Self::transfer_token_from(
+                        e,
+                        spender,
+                        &from,
+                        &token,
+                        &config.fee_collector,
+                        fee,
+                    )?;
+                }
+            }
+            None => {
+                Self::transfer_deposit(e, &from, &token, &current_address, amount)?;
+                if fee > 0 {
+                    Self::transfer_deposit(e, &from, &token, &config.fee_collector, fee)?;
+                }
+            }
+        }
+        Ok((amount, fee, hub_amount))
+    }
+
+    /// Scales a local amount of `token` to the hub decimals, returning the scaled
+    /// amount and the local dust truncated by the conversion.
+    fn to_hub_amount(e: &Env, token: &Address, amount: u128) -> Result<(u128, u128), ContractError> {
+        match read_token_decimals(e, token) {
+            Some(decimals) => scale_amount(amount, decimals.local, decimals.hub)
+                .ok_or(ContractError::InvalidAmount),
+            None => Ok((amount, 0)),
+        }
+    }
+
+    /// Scales a hub amount of `token` to the local decimals. Amounts that can't be
+    /// paid out exactly are rejected rather than truncated.
+    fn from_hub_amount(e: &Env, token: &Address, amount: u128) -> Result<u128, ContractError> {
+        let decimals = match read_token_decimals(e, token) {
+            Some(decimals) => decimals,
+            None => return Ok(amount),
+        };
+        let (local_amount, dust) = scale_amount(amount, decimals.hub, decimals.local)
+            .ok_or(ContractError::InvalidAmount)?;
+        if dust > 0 {
+            return Err(ContractError::AmountHasDust);
+        }
+        Ok(local_amount)
+    }
+
+    fn send_deposit_call(
+        e: &Env,
+        config: &ConfigData,
+        from: &Address,
+        message_bytes: Bytes,
+        rollback_bytes: Bytes,
+    ) -> Result<u128, ContractError> {
         let message = AnyMessage::CallMessageWithRollback(CallMessageWithRollback {
             data: message_bytes,
             rollback: rollback_bytes,
         });
+        Self::send_call(e, config, from, message)
+    }
+
+    /// Sends `message` through xCall, which charges its fee to `from` in the
+    /// native token. The native balance of an account includes its minimum
+    /// reserve, which can't be spent, so a fee the account can't pay is only
+    /// caught by the transfer in xCall itself.
+    fn send_call(
+        e: &Env,
+        config: &ConfigData,
+        from: &Address,
+        message: AnyMessage,
+    ) -> Result<u128, ContractError> {
+        let (sources, destinations) =
+            Self::xcall_manager(e, &config.xcall_manager).get_protocols();
         let envelope: &Envelope = &Envelope {
             destinations,
             message,
             sources,
         };
 
-        Self::xcall_client(&e, &config.xcall).send_call(
-            &from,
-            &current_address,
+        match Self::xcall_client(e, &config.xcall).try_send_call(
+            from,
+            &e.current_contract_address(),
             envelope,
             &config.icon_asset_manager,
-        );
-        Ok(())
+        ) {
+            Ok(Ok(sn)) => Ok(sn),
+            _ => Err(ContractError::InsufficientNativeBalance),
+        }
     }
 
     fn xcall_manager(e: &Env, xcall_manager: &Address) -> XcallManagerClient<'static> {
@@ -283,31 +1754,73 @@ impl AssetManager {
                 return Err(ContractError::OnlyICONAssetManager);
             }
             let message = WithdrawTo::decode(&e, data);
-            if !is_valid_string_address(&message.to)
-                || !is_valid_string_address(&message.token_address)
-            {
+            if !is_valid_string_address(&message.token_address) {
                 return Err(ContractError::InvalidAddress);
             }
-            Self::withdraw(
+            let token = Address::from_string(&message.token_address);
+            let (to, mux_id) = Self::parse_recipient(&e, &message.to)?;
+            let amount = Self::from_hub_amount(&e, &token, message.amount)?;
+            events::withdraw_to(
                 &e,
-                current_contract,
-                Address::from_string(&message.token_address),
-                Address::from_string(&message.to),
-                message.amount,
-            )?;
+                token.clone(),
+                WithdrawEvent {
+                    to: to.clone(),
+                    amount,
+                },
+            );
+            if let Some(mux_id) = mux_id {
+                events::muxed_withdraw_to(&e, token.clone(), to.clone(), mux_id, amount);
+            }
+            Self::withdraw(&e, current_contract, token, to, amount, false)?;
         } else if method == String::from_str(&e, &DEPOSIT_REVERT_NAME) {
             let xcall_network_address = get_xcall_network_address(&e).unwrap();
             if xcall_network_address != from {
                 return Err(ContractError::OnlyCallService);
             }
             let message: DepositRevert = DepositRevert::decode(&e.clone(), data);
+            events::deposit_revert(
+                &e,
+                message.token_address.clone(),
+                WithdrawEvent {
+                    to: message.to.clone(),
+                    amount: message.amount,
+                },
+            );
             Self::withdraw(
                 &e,
                 current_contract,
                 message.token_address,
                 message.to,
                 message.amount,
+                true,
             )?;
+        } else if method == String::from_str(&e, DEPOSIT_BATCH_REVERT_NAME) {
+            let xcall_network_address = get_xcall_network_address(&e).unwrap();
+            if xcall_network_address != from {
+                return Err(ContractError::OnlyCallService);
+            }
+            let message = DepositBatchRevert::decode(&e, data);
+            if message.token_addresses.len() != message.amounts.len() {
+                return Err(ContractError::InvalidRollbackMessage);
+            }
+            for (token, amount) in message.token_addresses.iter().zip(message.amounts.iter()) {
+                events::deposit_revert(
+                    &e,
+                    token.clone(),
+                    WithdrawEvent {
+                        to: message.to.clone(),
+                        amount,
+                    },
+                );
+                Self::withdraw(
+                    &e,
+                    current_contract.clone(),
+                    token,
+                    message.to.clone(),
+                    amount,
+                    true,
+                )?;
+            }
         } else {
             return Err(ContractError::UnknownMessageType);
         }
@@ -323,14 +1836,109 @@ impl AssetManager {
         token: Address,
         to: Address,
         amount: u128,
+        refund: bool,
     ) -> Result<(), ContractError> {
         if amount <= 0 {
             return Err(ContractError::AmountIsLessThanMinimumAmount);
         }
 
+        if let Some(config) = Self::approval_config_for(e, &token, amount) {
+            let withdrawal = PendingWithdrawal {
+                token,
+                to,
+                amount,
+                unlock_time: e.ledger().timestamp() + config.max_delay,
+                refund,
+            };
+            let id = write_pending_withdrawal(e, &withdrawal);
+            write_withdrawal_approvals(e, id, &Vec::new(e));
+            events::withdrawal_awaiting_approval(e, id, withdrawal);
+            return Ok(());
+        }
+
+        // Refunds of reverted deposits are not subject to recipient limits.
+        let unlock_time = if Self::is_withdraw_paused(e.clone(), token.clone()) {
+            e.ledger().timestamp()
+        } else if !Self::can_withdraw(e, token.clone(), amount)?
+            || !(refund || Self::within_recipient_limit(e, &to, &token, amount))
+        {
+            e.ledger().timestamp() + read_withdrawal_delay(e)
+        } else {
+            if !refund {
+                Self::record_recipient_withdrawal(e, &to, &token, amount);
+            }
+            return Self::execute_withdraw(e, from, token, to, amount, refund);
+        };
+
+        let withdrawal = PendingWithdrawal {
+            token,
+            to,
+            amount,
+            unlock_time,
+            refund,
+        };
+        let id = write_pending_withdrawal(e, &withdrawal);
+        events::withdrawal_queued(e, id, withdrawal);
+        Ok(())
+    }
+
+    /// Resolves the recipient of a `WithdrawTo`. A muxed `M...` address pays its
+    /// underlying account and returns its id along with it.
+    fn parse_recipient(e: &Env, to: &String) -> Result<(Address, Option<u64>), ContractError> {
+        match decode_string_address(to).map_err(|_| ContractError::InvalidAddress)? {
+            StrKey::Muxed { ed25519, id } => {
+                Ok((Address::from_string(&account_address(e, &ed25519)), Some(id)))
+            }
+            _ => Ok((Address::from_string(to), None)),
+        }
+    }
+
+    /// Returns the approval config if a withdrawal of `amount` of `token` has to
+    /// be approved by the guardians.
+    fn approval_config_for(e: &Env, token: &Address, amount: u128) -> Option<ApprovalConfig> {
+        let threshold = read_approval_threshold(e, token)?;
+        if amount <= threshold {
+            return None;
+        }
+        get_approval_config(e)
+    }
+
+    fn execute_withdraw(
+        e: &Env,
+        from: Address,
+        token: Address,
+        to: Address,
+        amount: u128,
+        refund: bool,
+    ) -> Result<(), ContractError> {
         let verified = Self::verify_withdraw(e.clone(), token.clone(), amount)?;
         if verified {
-            Self::transfer_token_to(e, from, token, to, amount)?;
+            if amount > i128::MAX as u128 {
+                return Err(ContractError::InvalidAmount);
+            }
+            Self::update_token_stats(e, &token, |stats| {
+                let now = e.ledger().timestamp();
+                if refund {
+                    stats.refunded += amount;
+                    stats.refund_count += 1;
+                    stats.last_refund = now;
+                } else {
+                    stats.withdrawn += amount;
+                    stats.withdrawal_count += 1;
+                    stats.last_withdrawal = now;
+                }
+            });
+            // A failed payout (missing trustline, frozen or deauthorized account)
+            // must not fail the xCall message, so the amount is escrowed instead.
+            let token_client = token::Client::new(e, &token);
+            if let Ok(Ok(())) = token_client.try_transfer(&from, &to, &(amount as i128)) {
+                Self::record_flow(e, &token, 0, amount);
+                events::withdrawn(e, token, WithdrawEvent { to, amount });
+            } else {
+                add_claimable(e, &to, &token, amount);
+                Self::update_token_stats(e, &token, |stats| stats.pending += amount);
+                events::payout_escrowed(e, token, WithdrawEvent { to, amount });
+            }
         }
         Ok(())
     }
@@ -345,6 +1953,146 @@ impl AssetManager {
         Ok(())
     }
 
+    fn transfer_token_from(
+        e: &Env,
+        spender: &Address,
+        from: &Address,
+        token: &Address,
+        to: &Address,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        if amount > i128::MAX as u128 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let token_client = token::Client::new(e, token);
+        if *token == get_config(e).native_address {
+            // The native balance includes the minimum reserve of `from`, so a
+            // deposit dipping into it is only caught by the transfer itself.
+            return match token_client.try_transfer_from(spender, from, to, &(amount as i128)) {
+                Ok(Ok(())) => Ok(()),
+                _ => Err(ContractError::InsufficientNativeBalance),
+            };
+        }
+        token_client.transfer_from(spender, from, to, &(amount as i128));
+        Ok(())
+    }
+
+    /// Moves a deposit from `from`, which authorized it, to `to`.
+    fn transfer_deposit(
+        e: &Env,
+        from: &Address,
+        token: &Address,
+        to: &Address,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        if amount > i128::MAX as u128 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let token_client = token::Client::new(e, token);
+        if *token == get_config(e).native_address {
+            // The native balance includes the minimum reserve of `from`, so a
+            // deposit dipping into it is only caught by the transfer itself.
+            return match token_client.try_transfer(from, to, &(amount as i128)) {
+                Ok(Ok(())) => Ok(()),
+                _ => Err(ContractError::InsufficientNativeBalance),
+            };
+        }
+        token_client.transfer(from, to, &(amount as i128));
+        Ok(())
+    }
+
+    /// Sends the balance of each of `tokens` to the ICON asset manager, along with
+    /// the amounts deposited and withdrawn since the previous report, so the hub
+    /// can reconcile them against what it credited. Anyone can report, `from`
+    /// pays the xCall fee, which `quote_deposit_fee(false)` quotes. Amounts are
+    /// scaled to the hub decimals.
+    pub fn report_reserves(
+        e: Env,
+        from: Address,
+        tokens: Vec<Address>,
+    ) -> Result<(), ContractError> {
+        from.require_auth();
+        if tokens.is_empty() {
+            return Err(ContractError::EmptyBatch);
+        }
+        let registered = read_tokens(&e);
+        let mut reported: Vec<Address> = Vec::new(&e);
+        let mut token_addresses: Vec<String> = Vec::new(&e);
+        let mut balances: Vec<u128> = Vec::new(&e);
+        let mut deposited: Vec<u128> = Vec::new(&e);
+        let mut withdrawn: Vec<u128> = Vec::new(&e);
+        for token in tokens.iter() {
+            if !registered.contains(&token) {
+                return Err(ContractError::TokenDoesNotExists);
+            }
+            if reported.contains(&token) {
+                continue;
+            }
+            reported.push_back(token.clone());
+            let flow = read_reserve_flow(&e, &token);
+            let balance = Self::get_token_balance(&e, token.clone());
+            token_addresses.push_back(token.to_string());
+            balances.push_back(Self::to_hub_amount(&e, &token, balance)?.0);
+            deposited.push_back(Self::to_hub_amount(&e, &token, flow.deposited)?.0);
+            withdrawn.push_back(Self::to_hub_amount(&e, &token, flow.withdrawn)?.0);
+        }
+
+        let config = get_config(&e);
+        let report = ReserveReport::new(token_addresses, balances, deposited, withdrawn);
+        let message = AnyMessage::CallMessage(CallMessage {
+            data: report.encode(&e, String::from_str(&e, RESERVE_REPORT_NAME)),
+        });
+        let sn = Self::send_call(&e, &config, &from, message)?;
+
+        for token in reported.iter() {
+            let flow = read_reserve_flow(&e, &token);
+            remove_reserve_flow(&e, &token);
+            events::reserve_reported(&e, token, sn, flow);
+        }
+        Ok(())
+    }
+
+    /// Returns the amounts of `token` deposited and withdrawn since its reserves
+    /// were last reported.
+    pub fn get_reserve_flow(e: Env, token: Address) -> ReserveFlow {
+        read_reserve_flow(&e, &token)
+    }
+
+    fn record_flow(e: &Env, token: &Address, deposited: u128, withdrawn: u128) {
+        let mut flow = read_reserve_flow(e, token);
+        flow.deposited += deposited;
+        flow.withdrawn += withdrawn;
+        write_reserve_flow(e, token, &flow);
+    }
+
+    /// Returns the lifetime deposits, withdrawals and refunds of `token`.
+    pub fn get_token_stats(e: Env, token: Address) -> TokenStats {
+        read_token_stats(&e, &token)
+    }
+
+    /// Compares the balance of `token` with the balance expected from its
+    /// lifetime accounting. A surplus is held but not owed to anyone, such as
+    /// a direct transfer to the contract or funds held before the accounting
+    /// was introduced.
+    pub fn reconcile(e: Env, token: Address) -> Reconciliation {
+        let stats = read_token_stats(&e, &token);
+        let balance = Self::get_token_balance(&e, token);
+        let expected =
+            (stats.deposited + stats.pending).saturating_sub(stats.withdrawn + stats.refunded);
+        Reconciliation {
+            balance,
+            expected,
+            surplus: balance.saturating_sub(expected),
+            shortfall: expected.saturating_sub(balance),
+        }
+    }
+
+    fn update_token_stats(e: &Env, token: &Address, update: impl FnOnce(&mut TokenStats)) {
+        let mut stats = read_token_stats(e, token);
+        update(&mut stats);
+        write_token_stats(e, token, &stats);
+    }
+
     pub fn balance_of(e: Env, token: Address) -> i128 {
         let token_client = token::Client::new(&e, &token);
         return token_client.balance(&e.current_contract_address());
@@ -354,20 +2102,136 @@ impl AssetManager {
         has_registry(&e)
     }
 
-    pub fn set_upgrade_authority(e: Env, upgrade_authority: Address) {
+    pub fn propose_upgrade_authority(e: Env, upgrade_authority: Address) {
+        let config = config::get_config(&e);
+        config.upgrade_authority.require_auth();
+
+        let proposal = Self::new_proposal(&e, upgrade_authority);
+        write_proposal(&e, DataKey::PendingUpgradeAuthority, &proposal);
+        events::upgrade_authority_proposed(&e, proposal);
+    }
+
+    pub fn accept_upgrade_authority(e: Env) -> Result<(), ContractError> {
+        let proposal = Self::take_proposal(&e, DataKey::PendingUpgradeAuthority)?;
+
         let mut config = config::get_config(&e);
+        config.upgrade_authority = proposal.address.clone();
+        config::set_config(&e, config);
+        events::upgrade_authority_changed(&e, proposal.address);
+        Ok(())
+    }
 
+    pub fn cancel_upgrade_auth_proposal(e: Env) -> Result<(), ContractError> {
+        let config = config::get_config(&e);
         config.upgrade_authority.require_auth();
 
-        config.upgrade_authority = upgrade_authority;
-        config::set_config(&e, config);
+        let proposal = read_proposal(&e, DataKey::PendingUpgradeAuthority)
+            .ok_or(ContractError::NoPendingProposal)?;
+        remove_proposal(&e, DataKey::PendingUpgradeAuthority);
+        events::upgrade_authority_cancelled(&e, proposal.address);
+        Ok(())
+    }
+
+    pub fn get_pending_upgrade_authority(e: Env) -> Option<PendingAddress> {
+        read_proposal(&e, DataKey::PendingUpgradeAuthority)
+    }
+
+    fn new_proposal(e: &Env, address: Address) -> PendingAddress {
+        PendingAddress {
+            address,
+            expires_at: e.ledger().timestamp() + PROPOSAL_EXPIRY,
+        }
+    }
+
+    fn take_proposal(e: &Env, key: DataKey) -> Result<PendingAddress, ContractError> {
+        let proposal = read_proposal(e, key.clone()).ok_or(ContractError::NoPendingProposal)?;
+        if e.ledger().timestamp() > proposal.expires_at {
+            return Err(ContractError::ProposalExpired);
+        }
+        proposal.address.require_auth();
+
+        remove_proposal(e, key);
+        Ok(proposal)
     }
 
-    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+    /// Sets how long a scheduled upgrade has to wait before it can be applied.
+    /// Fails with `InvalidAmount` below `MIN_UPGRADE_DELAY`.
+    pub fn set_upgrade_delay(e: Env, delay: u64) -> Result<(), ContractError> {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+        if delay < MIN_UPGRADE_DELAY {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        write_upgrade_delay(&e, delay);
+        events::upgrade_delay_updated(&e, delay);
+        Ok(())
+    }
+
+    pub fn get_upgrade_delay(e: Env) -> u64 {
+        read_upgrade_delay(&e)
+    }
+
+    pub fn get_scheduled_upgrade(e: Env) -> Option<ScheduledUpgrade> {
+        read_scheduled_upgrade(&e)
+    }
+
+    pub fn schedule_upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        let config = get_config(&e);
+        config.upgrade_authority.require_auth();
+
+        if read_scheduled_upgrade(&e).is_some() {
+            return Err(ContractError::UpgradeAlreadyScheduled);
+        }
+        let upgrade = ScheduledUpgrade {
+            wasm_hash: new_wasm_hash,
+            executable_at: e.ledger().timestamp() + read_upgrade_delay(&e),
+        };
+        write_scheduled_upgrade(&e, &upgrade);
+        events::upgrade_scheduled(&e, upgrade);
+        Ok(())
+    }
+
+    pub fn execute_upgrade(e: Env) -> Result<(), ContractError> {
         let config = get_config(&e);
         config.upgrade_authority.require_auth();
 
-        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        let upgrade = read_scheduled_upgrade(&e).ok_or(ContractError::UpgradeNotScheduled)?;
+        if e.ledger().timestamp() < upgrade.executable_at {
+            return Err(ContractError::UpgradeLocked);
+        }
+        remove_scheduled_upgrade(&e);
+
+        e.deployer().update_current_contract_wasm(upgrade.wasm_hash.clone());
+        events::upgraded(&e, upgrade.wasm_hash);
+        Ok(())
+    }
+
+    pub fn cancel_upgrade(e: Env, caller: Address) -> Result<(), ContractError> {
+        Self::require_admin_or_guardian(&e, &caller)?;
+
+        let upgrade = read_scheduled_upgrade(&e).ok_or(ContractError::UpgradeNotScheduled)?;
+        remove_scheduled_upgrade(&e);
+        events::upgrade_cancelled(&e, upgrade.wasm_hash, caller);
+        Ok(())
+    }
+
+    pub fn version(e: Env) -> u32 {
+        read_schema_version(&e)
+    }
+
+    pub fn migrate(e: Env) -> Result<(), ContractError> {
+        let upgrade_authority = migration::read_upgrade_authority(&e);
+        upgrade_authority.require_auth();
+
+        let from = read_schema_version(&e);
+        if from >= migration::SCHEMA_VERSION {
+            return Err(ContractError::AlreadyMigrated);
+        }
+        migration::migrate(&e, from);
+        write_schema_version(&e, migration::SCHEMA_VERSION);
+        events::migrated(&e, from, migration::SCHEMA_VERSION);
+        Ok(())
     }
 
     pub fn extend_ttl(e: Env) {
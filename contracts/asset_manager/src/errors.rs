@@ -19,5 +19,39 @@ pub enum ContractError {
     InvalidAddress = 13,
     TokenDoesNotExists = 14,
     InvalidAmount = 15,
-    Uninitialized = 16
+    Uninitialized = 16,
+    Paused = 17,
+    GuardianNotSet = 18,
+    WithdrawalNotFound = 19,
+    WithdrawalLocked = 20,
+    WithdrawalUnlocked = 21,
+    OnlyAdminOrGuardian = 22,
+    NoPendingProposal = 23,
+    ProposalExpired = 24,
+    UpgradeAlreadyScheduled = 25,
+    UpgradeNotScheduled = 26,
+    UpgradeLocked = 27,
+    AlreadyMigrated = 28,
+    DepositFeeNotSet = 29,
+    InvalidNetworkAddress = 30,
+    EmptyBatch = 31,
+    RescuerNotSet = 32,
+    NothingToClaim = 33,
+    OnlyRecipientOrRescuer = 34,
+    InvalidDecimals = 35,
+    AmountHasDust = 36,
+    TokenDecimalsNotSet = 37,
+    ExceedsDepositLimit = 38,
+    DepositLimitNotSet = 39,
+    RateLimitWindowExists = 40,
+    RateLimitWindowNotFound = 41,
+    RateLimitGroupNotFound = 42,
+    TokenAlreadyInRateLimitGroup = 43,
+    TokenNotInRateLimitGroup = 44,
+    InvalidApprovalConfig = 45,
+    ApprovalsNotConfigured = 46,
+    OnlyApprovalGuardian = 47,
+    AlreadyApproved = 48,
+    WithdrawalNotAwaitingApproval = 49,
+    InsufficientNativeBalance = 50
 }
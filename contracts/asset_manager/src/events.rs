@@ -0,0 +1,378 @@
+//! Events published by the AssetManager.
+//!
+//! Every event is published with the topics `(name, EVENT_VERSION, ..keys)`, where
+//! `name` is the symbol listed below and `EVENT_VERSION` is bumped whenever the
+//! topics or data of an existing event change shape.
+//!
+//! | name                            | keys              | data                      |
+//! |---------------------------------|-------------------|---------------------------|
+//! | `deposit`                       | token             | [`DepositEvent`]          |
+//! | `withdraw_to`                   | token             | [`WithdrawEvent`]         |
+//! | `muxed_withdraw_to`             | token             | `(to, mux_id, amount)`    |
+//! | `deposit_revert`                | token             | [`WithdrawEvent`]         |
+//! | `withdrawn`                     | token             | [`WithdrawEvent`]         |
+//! | `withdrawal_queued`             | id                | `PendingWithdrawal`       |
+//! | `withdrawal_claimed`            | id                | `()`                      |
+//! | `withdrawal_cancelled`          | id                | caller                    |
+//! | `withdrawal_awaiting_approval`  | id                | `PendingWithdrawal`       |
+//! | `withdrawal_approved`           | id                | `(guardian, approvals)`   |
+//! | `withdrawal_released`           | id                | `()`                      |
+//! | `withdrawal_rejected`           | id                | guardian                  |
+//! | `payout_escrowed`               | token             | [`WithdrawEvent`]         |
+//! | `claimable_claimed`             | token             | `(recipient, to, amount)` |
+//! | `reserve_reported`              | token             | `(sn, ReserveFlow)`       |
+//! | `rate_limit_configured`         | token             | `(period, percentage)`    |
+//! | `rate_limit_updated`            | token             | `(period, percentage)`    |
+//! | `rate_limit_removed`            | token             | `()`                      |
+//! | `rate_limit_window_added`       | token             | `(period, percentage)`    |
+//! | `rate_limit_window_removed`     | token             | period                    |
+//! | `rate_limit_group_configured`   | group             | `(period, percentage)`    |
+//! | `rate_limit_group_removed`      | group             | `()`                      |
+//! | `rate_limit_group_joined`       | token             | group                     |
+//! | `rate_limit_group_left`         | token             | group                     |
+//! | `withdraw_cap_updated`          | token             | max_withdraw              |
+//! | `recipient_limit_updated`       | token             | `RecipientLimit`          |
+//! | `recipient_exemption_updated`   | recipient         | exempt                    |
+//! | `approval_threshold_updated`    | token             | threshold                 |
+//! | `deposit_limit_updated`         | token             | `(period, max_deposit)`   |
+//! | `deposit_limit_removed`         | token             | `()`                      |
+//! | `deposit_fee_updated`           | token             | `DepositFee`              |
+//! | `deposit_fee_removed`           | token             | `()`                      |
+//! | `token_decimals_updated`        | token             | `TokenDecimals`           |
+//! | `token_decimals_removed`        | token             | `()`                      |
+//! | `limit_reset`                   | token             | current_limit             |
+//! | `paused`                        | `Option<token>`   | `PauseState`              |
+//! | `unpaused`                      | `Option<token>`   | `PauseState`              |
+//! | `withdrawal_delay_updated`      |                   | delay                     |
+//! | `admin_proposed`                |                   | `PendingAddress`          |
+//! | `admin_proposal_cancelled`      |                   | proposed admin            |
+//! | `admin_changed`                 |                   | new admin                 |
+//! | `guardian_changed`              |                   | new guardian              |
+//! | `rescuer_changed`               |                   | new rescuer               |
+//! | `configured`                    |                   | `ConfigData`              |
+//! | `approvals_configured`          |                   | `ApprovalConfig`          |
+//! | `xcall_network_address_updated` |                   | network address           |
+//! | `upgrade_authority_proposed`    |                   | `PendingAddress`          |
+//! | `upgrade_authority_cancelled`   |                   | proposed authority        |
+//! | `upgrade_authority_changed`     |                   | new upgrade authority     |
+//! | `upgrade_delay_updated`         |                   | delay                     |
+//! | `upgrade_scheduled`             |                   | `ScheduledUpgrade`        |
+//! | `upgrade_cancelled`             |                   | `(wasm hash, caller)`     |
+//! | `upgraded`                      |                   | new wasm hash             |
+//! | `migrated`                      |                   | `(from, to)` versions     |
+//!
+//! `withdraw_to` and `deposit_revert` are published when the xCall message is
+//! handled; `withdrawn` is published when tokens actually leave the contract,
+//! either right away or when a queued withdrawal is claimed. If that transfer
+//! fails, `payout_escrowed` is published instead and the amount can later be
+//! taken with `claim`, which publishes `claimable_claimed`.
+//!
+//! When the recipient of a `WithdrawTo` is a muxed `M...` address, its
+//! underlying account is paid and `muxed_withdraw_to` follows `withdraw_to` with
+//! the mux id, so exchanges can credit the right customer.
+//!
+//! Withdrawals above the approval threshold of their token are queued with
+//! `withdrawal_awaiting_approval` instead. Each guardian approval publishes
+//! `withdrawal_approved` and the last one required `withdrawal_released`, after
+//! which the withdrawal can be claimed. Without enough approvals it unlocks on
+//! its own after the maximum delay.
+//!
+//! `report_reserves` publishes `reserve_reported` for every reported token with
+//! the amounts that entered and left the contract since its previous report.
+//!
+//! Version 2 added the `fee` to [`DepositEvent`] and the `fee_collector` to the
+//! `configured` data.
+use soroban_sdk::{contracttype, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec};
+
+use crate::{
+    config::{ApprovalConfig, ConfigData},
+    storage_types::{
+        DepositFee, PauseState, PendingAddress, PendingWithdrawal, RecipientLimit, ReserveFlow,
+        ScheduledUpgrade, TokenDecimals,
+    },
+};
+
+pub const EVENT_VERSION: u32 = 2;
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DepositEvent {
+    pub from: Address,
+    pub to: String,
+    pub amount: u128,
+    pub fee: u128,
+    pub sn: u128,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct WithdrawEvent {
+    pub to: Address,
+    pub amount: u128,
+}
+
+fn publish<K, D>(e: &Env, name: &str, key: Option<K>, data: D)
+where
+    K: IntoVal<Env, Val>,
+    D: IntoVal<Env, Val>,
+{
+    let mut topics: Vec<Val> = Vec::new(e);
+    topics.push_back(Symbol::new(e, name).into_val(e));
+    topics.push_back(EVENT_VERSION.into_val(e));
+    if let Some(key) = key {
+        topics.push_back(key.into_val(e));
+    }
+    e.events().publish(topics, data);
+}
+
+pub(crate) fn deposit(e: &Env, token: Address, event: DepositEvent) {
+    publish(e, "deposit", Some(token), event);
+}
+
+pub(crate) fn withdraw_to(e: &Env, token: Address, event: WithdrawEvent) {
+    publish(e, "withdraw_to", Some(token), event);
+}
+
+pub(crate) fn muxed_withdraw_to(e: &Env, token: Address, to: Address, mux_id: u64, amount: u128) {
+    publish(e, "muxed_withdraw_to", Some(token), (to, mux_id, amount));
+}
+
+pub(crate) fn deposit_revert(e: &Env, token: Address, event: WithdrawEvent) {
+    publish(e, "deposit_revert", Some(token), event);
+}
+
+pub(crate) fn withdrawn(e: &Env, token: Address, event: WithdrawEvent) {
+    publish(e, "withdrawn", Some(token), event);
+}
+
+pub(crate) fn payout_escrowed(e: &Env, token: Address, event: WithdrawEvent) {
+    publish(e, "payout_escrowed", Some(token), event);
+}
+
+pub(crate) fn claimable_claimed(
+    e: &Env,
+    token: Address,
+    recipient: Address,
+    to: Address,
+    amount: u128,
+) {
+    publish(e, "claimable_claimed", Some(token), (recipient, to, amount));
+}
+
+pub(crate) fn reserve_reported(e: &Env, token: Address, sn: u128, flow: ReserveFlow) {
+    publish(e, "reserve_reported", Some(token), (sn, flow));
+}
+
+pub(crate) fn withdrawal_queued(e: &Env, id: u64, withdrawal: PendingWithdrawal) {
+    publish(e, "withdrawal_queued", Some(id), withdrawal);
+}
+
+pub(crate) fn withdrawal_claimed(e: &Env, id: u64) {
+    publish(e, "withdrawal_claimed", Some(id), ());
+}
+
+pub(crate) fn withdrawal_cancelled(e: &Env, id: u64, caller: Address) {
+    publish(e, "withdrawal_cancelled", Some(id), caller);
+}
+
+pub(crate) fn withdrawal_awaiting_approval(e: &Env, id: u64, withdrawal: PendingWithdrawal) {
+    publish(e, "withdrawal_awaiting_approval", Some(id), withdrawal);
+}
+
+pub(crate) fn withdrawal_approved(e: &Env, id: u64, guardian: Address, approvals: u32) {
+    publish(e, "withdrawal_approved", Some(id), (guardian, approvals));
+}
+
+pub(crate) fn withdrawal_released(e: &Env, id: u64) {
+    publish(e, "withdrawal_released", Some(id), ());
+}
+
+pub(crate) fn withdrawal_rejected(e: &Env, id: u64, guardian: Address) {
+    publish(e, "withdrawal_rejected", Some(id), guardian);
+}
+
+pub(crate) fn rate_limit_configured(e: &Env, token: Address, period: u64, percentage: u32) {
+    publish(
+        e,
+        "rate_limit_configured",
+        Some(token),
+        (period, percentage),
+    );
+}
+
+pub(crate) fn rate_limit_updated(e: &Env, token: Address, period: u64, percentage: u32) {
+    publish(e, "rate_limit_updated", Some(token), (period, percentage));
+}
+
+pub(crate) fn rate_limit_removed(e: &Env, token: Address) {
+    publish(e, "rate_limit_removed", Some(token), ());
+}
+
+pub(crate) fn rate_limit_window_added(e: &Env, token: Address, period: u64, percentage: u32) {
+    publish(
+        e,
+        "rate_limit_window_added",
+        Some(token),
+        (period, percentage),
+    );
+}
+
+pub(crate) fn rate_limit_window_removed(e: &Env, token: Address, period: u64) {
+    publish(e, "rate_limit_window_removed", Some(token), period);
+}
+
+pub(crate) fn rate_limit_group_configured(e: &Env, group: Symbol, period: u64, percentage: u32) {
+    publish(
+        e,
+        "rate_limit_group_configured",
+        Some(group),
+        (period, percentage),
+    );
+}
+
+pub(crate) fn rate_limit_group_removed(e: &Env, group: Symbol) {
+    publish(e, "rate_limit_group_removed", Some(group), ());
+}
+
+pub(crate) fn rate_limit_group_joined(e: &Env, token: Address, group: Symbol) {
+    publish(e, "rate_limit_group_joined", Some(token), group);
+}
+
+pub(crate) fn rate_limit_group_left(e: &Env, token: Address, group: Symbol) {
+    publish(e, "rate_limit_group_left", Some(token), group);
+}
+
+pub(crate) fn approval_threshold_updated(e: &Env, token: Address, threshold: u128) {
+    publish(e, "approval_threshold_updated", Some(token), threshold);
+}
+
+pub(crate) fn recipient_limit_updated(e: &Env, token: Address, limit: RecipientLimit) {
+    publish(e, "recipient_limit_updated", Some(token), limit);
+}
+
+pub(crate) fn recipient_exemption_updated(e: &Env, recipient: Address, exempt: bool) {
+    publish(e, "recipient_exemption_updated", Some(recipient), exempt);
+}
+
+pub(crate) fn withdraw_cap_updated(e: &Env, token: Address, max_withdraw: u128) {
+    publish(e, "withdraw_cap_updated", Some(token), max_withdraw);
+}
+
+pub(crate) fn deposit_limit_updated(e: &Env, token: Address, period: u64, max_deposit: u128) {
+    publish(
+        e,
+        "deposit_limit_updated",
+        Some(token),
+        (period, max_deposit),
+    );
+}
+
+pub(crate) fn deposit_limit_removed(e: &Env, token: Address) {
+    publish(e, "deposit_limit_removed", Some(token), ());
+}
+
+pub(crate) fn deposit_fee_updated(e: &Env, token: Address, fee: DepositFee) {
+    publish(e, "deposit_fee_updated", Some(token), fee);
+}
+
+pub(crate) fn deposit_fee_removed(e: &Env, token: Address) {
+    publish(e, "deposit_fee_removed", Some(token), ());
+}
+
+pub(crate) fn token_decimals_updated(e: &Env, token: Address, decimals: TokenDecimals) {
+    publish(e, "token_decimals_updated", Some(token), decimals);
+}
+
+pub(crate) fn token_decimals_removed(e: &Env, token: Address) {
+    publish(e, "token_decimals_removed", Some(token), ());
+}
+
+pub(crate) fn limit_reset(e: &Env, token: Address, current_limit: u64) {
+    publish(e, "limit_reset", Some(token), current_limit);
+}
+
+pub(crate) fn paused(e: &Env, token: Option<Address>, state: PauseState) {
+    publish(e, "paused", Some(token), state);
+}
+
+pub(crate) fn unpaused(e: &Env, token: Option<Address>, state: PauseState) {
+    publish(e, "unpaused", Some(token), state);
+}
+
+pub(crate) fn withdrawal_delay_updated(e: &Env, delay: u64) {
+    publish(e, "withdrawal_delay_updated", None::<()>, delay);
+}
+
+pub(crate) fn admin_proposed(e: &Env, proposal: PendingAddress) {
+    publish(e, "admin_proposed", None::<()>, proposal);
+}
+
+pub(crate) fn admin_proposal_cancelled(e: &Env, proposed: Address) {
+    publish(e, "admin_proposal_cancelled", None::<()>, proposed);
+}
+
+pub(crate) fn admin_changed(e: &Env, new_admin: Address) {
+    publish(e, "admin_changed", None::<()>, new_admin);
+}
+
+pub(crate) fn guardian_changed(e: &Env, guardian: Address) {
+    publish(e, "guardian_changed", None::<()>, guardian);
+}
+
+pub(crate) fn rescuer_changed(e: &Env, rescuer: Address) {
+    publish(e, "rescuer_changed", None::<()>, rescuer);
+}
+
+pub(crate) fn configured(e: &Env, config: ConfigData) {
+    publish(e, "configured", None::<()>, config);
+}
+
+pub(crate) fn approvals_configured(e: &Env, config: ApprovalConfig) {
+    publish(e, "approvals_configured", None::<()>, config);
+}
+
+pub(crate) fn xcall_network_address_updated(e: &Env, network_address: String) {
+    publish(
+        e,
+        "xcall_network_address_updated",
+        None::<()>,
+        network_address,
+    );
+}
+
+pub(crate) fn upgrade_authority_proposed(e: &Env, proposal: PendingAddress) {
+    publish(e, "upgrade_authority_proposed", None::<()>, proposal);
+}
+
+pub(crate) fn upgrade_authority_cancelled(e: &Env, proposed: Address) {
+    publish(e, "upgrade_authority_cancelled", None::<()>, proposed);
+}
+
+pub(crate) fn upgrade_authority_changed(e: &Env, upgrade_authority: Address) {
+    publish(
+        e,
+        "upgrade_authority_changed",
+        None::<()>,
+        upgrade_authority,
+    );
+}
+
+pub(crate) fn upgrade_delay_updated(e: &Env, delay: u64) {
+    publish(e, "upgrade_delay_updated", None::<()>, delay);
+}
+
+pub(crate) fn upgrade_scheduled(e: &Env, upgrade: ScheduledUpgrade) {
+    publish(e, "upgrade_scheduled", None::<()>, upgrade);
+}
+
+pub(crate) fn upgrade_cancelled(e: &Env, wasm_hash: BytesN<32>, caller: Address) {
+    publish(e, "upgrade_cancelled", None::<()>, (wasm_hash, caller));
+}
+
+pub(crate) fn upgraded(e: &Env, new_wasm_hash: BytesN<32>) {
+    publish(e, "upgraded", None::<()>, new_wasm_hash);
+}
+
+pub(crate) fn migrated(e: &Env, from: u32, to: u32) {
+    publish(e, "migrated", None::<()>, (from, to));
+}
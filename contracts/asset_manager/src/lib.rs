@@ -6,4 +6,6 @@ pub mod tests;
 pub mod states;
 mod config;
 mod errors;
+mod events;
+mod migration;
 mod xcall_manager_interface;
\ No newline at end of file
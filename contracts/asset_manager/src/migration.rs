@@ -0,0 +1,162 @@
+//! Storage migrations applied by `migrate` after an upgrade.
+//!
+//! Contracts deployed before schema versioning was introduced have no stored
+//! version and are treated as version 0. Each step moves the storage from
+//! `version` to `version + 1` and runs exactly once.
+use soroban_sdk::{contracttype, Address, Env, Map, String, Symbol, TryFromVal, Val, Vec};
+
+use crate::{
+    config::{set_config, ConfigData},
+    states::{index_claimable, read_administrator, read_tokens, write_token_data},
+    storage_types::{DataKey, PendingWithdrawal, TokenData},
+};
+
+pub(crate) const SCHEMA_VERSION: u32 = 5;
+
+/// `TokenData` as stored before withdraw caps were introduced.
+#[contracttype]
+struct TokenDataV0 {
+    period: u64,
+    percentage: u32,
+    last_update: u64,
+    current_limit: u64,
+}
+
+/// `TokenData` as stored before additional rate limit windows were introduced.
+#[contracttype]
+struct TokenDataV1 {
+    period: u64,
+    percentage: u32,
+    last_update: u64,
+    current_limit: u64,
+    max_withdraw: u128,
+    withdrawn: u128,
+}
+
+/// `PendingWithdrawal` as stored before refunds were told apart.
+#[contracttype]
+struct PendingWithdrawalV4 {
+    token: Address,
+    to: Address,
+    amount: u128,
+    unlock_time: u64,
+}
+
+/// `ConfigData` as stored before deposit fees were introduced.
+#[contracttype]
+struct ConfigDataV1 {
+    xcall: Address,
+    xcall_manager: Address,
+    native_address: Address,
+    icon_asset_manager: String,
+    upgrade_authority: Address,
+}
+
+/// Reads the upgrade authority from `ConfigData` regardless of the shape it was
+/// stored with, so `migrate` can be authorized before the config is migrated.
+pub(crate) fn read_upgrade_authority(e: &Env) -> Address {
+    let config: Map<Symbol, Val> = e.storage().instance().get(&DataKey::Config).unwrap();
+    let upgrade_authority = config.get(Symbol::new(e, "upgrade_authority")).unwrap();
+    Address::try_from_val(e, &upgrade_authority).unwrap()
+}
+
+pub(crate) fn migrate(e: &Env, from: u32) {
+    for version in from..SCHEMA_VERSION {
+        match version {
+            0 => migrate_token_data_v0(e),
+            1 => migrate_config_v1(e),
+            2 => migrate_token_data_v1(e),
+            3 => migrate_claimables_v3(e),
+            4 => migrate_pending_withdrawals_v4(e),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn migrate_token_data_v0(e: &Env) {
+    for token in read_tokens(e).iter() {
+        let key = DataKey::TokenData(token.clone());
+        let legacy: Option<TokenDataV0> = e.storage().persistent().get(&key);
+        if let Some(legacy) = legacy {
+            let data = TokenDataV1 {
+                period: legacy.period,
+                percentage: legacy.percentage,
+                last_update: legacy.last_update,
+                current_limit: legacy.current_limit,
+                max_withdraw: 0,
+                withdrawn: 0,
+            };
+            e.storage().persistent().set(&key, &data);
+        }
+    }
+}
+
+/// Fees are collected by the admin until a fee collector is configured.
+fn migrate_config_v1(e: &Env) {
+    let legacy: ConfigDataV1 = e.storage().instance().get(&DataKey::Config).unwrap();
+    let config = ConfigData {
+        xcall: legacy.xcall,
+        xcall_manager: legacy.xcall_manager,
+        native_address: legacy.native_address,
+        icon_asset_manager: legacy.icon_asset_manager,
+        upgrade_authority: legacy.upgrade_authority,
+        fee_collector: read_administrator(e),
+    };
+    set_config(e, config);
+}
+
+fn migrate_token_data_v1(e: &Env) {
+    for token in read_tokens(e).iter() {
+        let key = DataKey::TokenData(token.clone());
+        let legacy: Option<TokenDataV1> = e.storage().persistent().get(&key);
+        if let Some(legacy) = legacy {
+            let data = TokenData {
+                period: legacy.period,
+                percentage: legacy.percentage,
+                last_update: legacy.last_update,
+                current_limit: legacy.current_limit,
+                max_withdraw: legacy.max_withdraw,
+                withdrawn: legacy.withdrawn,
+                windows: Vec::new(e),
+            };
+            write_token_data(e, token, data);
+        }
+    }
+}
+
+/// Claimables used to be listed in a single `Claimables` entry, they are now
+/// indexed by id.
+fn migrate_claimables_v3(e: &Env) {
+    let legacy: Option<Vec<(Address, Address)>> =
+        e.storage().persistent().get(&DataKey::Claimables);
+    if let Some(legacy) = legacy {
+        for (recipient, token) in legacy.iter() {
+            index_claimable(e, &recipient, &token);
+        }
+        e.storage().persistent().remove(&DataKey::Claimables);
+    }
+}
+
+/// Queued withdrawals from before refunds were told apart are treated as
+/// withdrawals, so they go through the recipient limits when claimed.
+fn migrate_pending_withdrawals_v4(e: &Env) {
+    let last_id: u64 = e
+        .storage()
+        .instance()
+        .get(&DataKey::LastWithdrawalId)
+        .unwrap_or(0);
+    for id in 1..=last_id {
+        let key = DataKey::PendingWithdrawal(id);
+        let legacy: Option<PendingWithdrawalV4> = e.storage().persistent().get(&key);
+        if let Some(legacy) = legacy {
+            let withdrawal = PendingWithdrawal {
+                token: legacy.token,
+                to: legacy.to,
+                amount: legacy.amount,
+                unlock_time: legacy.unlock_time,
+                refund: false,
+            };
+            e.storage().persistent().set(&key, &withdrawal);
+        }
+    }
+}
@@ -1,8 +1,16 @@
-use soroban_sdk::{Address, Env, Vec, String};
+use soroban_sdk::{Address, Env, Vec, String, Symbol};
 
-use crate::{errors::ContractError, storage_types::{DataKey, TokenData}};
+use crate::{
+    errors::ContractError,
+    storage_types::{
+        Claimable, DataKey, DepositFee, DepositLimit, PauseState, PendingAddress, PendingWithdrawal,
+        RateLimitGroup, RecipientLimit, RecipientUsage, ReserveFlow, ScheduledUpgrade, TokenData,
+        TokenDecimals, TokenStats, DEFAULT_UPGRADE_DELAY, DEFAULT_WITHDRAWAL_DELAY,
+    },
+};
 
 pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+pub(crate) const LEDGER_CLOSE_TIME: u64 = 86400 / DAY_IN_LEDGERS as u64;
 pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
@@ -21,6 +29,18 @@ pub fn write_administrator(e: &Env, id: &Address) {
     e.storage().instance().set(&key, id);
 }
 
+pub fn read_proposal(e: &Env, key: DataKey) -> Option<PendingAddress> {
+    e.storage().instance().get(&key)
+}
+
+pub fn write_proposal(e: &Env, key: DataKey, proposal: &PendingAddress) {
+    e.storage().instance().set(&key, proposal);
+}
+
+pub fn remove_proposal(e: &Env, key: DataKey) {
+    e.storage().instance().remove(&key);
+}
+
 pub fn has_registry(e: &Env) -> bool {
     let key = DataKey::Registry;
     e.storage().instance().has(&key)
@@ -36,11 +56,537 @@ pub fn write_registry(e: &Env, id: &Address) {
     e.storage().instance().set(&key, id);
 }
 
+pub fn read_schema_version(e: &Env) -> u32 {
+    let key = DataKey::SchemaVersion;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_schema_version(e: &Env, version: u32) {
+    let key = DataKey::SchemaVersion;
+    e.storage().instance().set(&key, &version);
+}
+
+pub fn read_guardian(e: &Env) -> Result<Address, ContractError> {
+    let key = DataKey::Guardian;
+    e.storage()
+        .instance()
+        .get(&key)
+        .ok_or(ContractError::GuardianNotSet)
+}
+
+pub fn write_guardian(e: &Env, id: &Address) {
+    let key = DataKey::Guardian;
+    e.storage().instance().set(&key, id);
+}
+
+pub fn read_rescuer(e: &Env) -> Result<Address, ContractError> {
+    let key = DataKey::Rescuer;
+    e.storage()
+        .instance()
+        .get(&key)
+        .ok_or(ContractError::RescuerNotSet)
+}
+
+pub fn write_rescuer(e: &Env, id: &Address) {
+    let key = DataKey::Rescuer;
+    e.storage().instance().set(&key, id);
+}
+
+/// Reads the claimable balance of `recipient` and extends the TTL of it and of
+/// its index entries, claimables are not covered by `extent_ttl` since there is
+/// no bound on how many there are.
+pub fn read_claimable(e: &Env, recipient: &Address, token: &Address) -> u128 {
+    let key = DataKey::Claimable(recipient.clone(), token.clone());
+    let amount = match e.storage().persistent().get(&key) {
+        Some(amount) => amount,
+        None => return 0,
+    };
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+    let id_key = DataKey::ClaimableId(recipient.clone(), token.clone());
+    let id: Option<u64> = e.storage().persistent().get(&id_key);
+    if let Some(id) = id {
+        e.storage()
+            .persistent()
+            .extend_ttl(&id_key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        e.storage().persistent().extend_ttl(
+            &DataKey::ClaimableEntry(id),
+            INSTANCE_LIFETIME_THRESHOLD,
+            INSTANCE_BUMP_AMOUNT,
+        );
+    }
+    amount
+}
+
+pub fn read_last_claimable_id(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::LastClaimableId)
+        .unwrap_or(0)
+}
+
+/// Gives the claimable balance of `recipient` an id, so claimables can be
+/// listed page by page without a single index entry growing with each one.
+pub fn index_claimable(e: &Env, recipient: &Address, token: &Address) {
+    let id = read_last_claimable_id(e) + 1;
+    e.storage().instance().set(&DataKey::LastClaimableId, &id);
+
+    let entry_key = DataKey::ClaimableEntry(id);
+    e.storage()
+        .persistent()
+        .set(&entry_key, &(recipient.clone(), token.clone()));
+    e.storage().persistent().extend_ttl(
+        &entry_key,
+        INSTANCE_LIFETIME_THRESHOLD,
+        INSTANCE_BUMP_AMOUNT,
+    );
+
+    let id_key = DataKey::ClaimableId(recipient.clone(), token.clone());
+    e.storage().persistent().set(&id_key, &id);
+    e.storage()
+        .persistent()
+        .extend_ttl(&id_key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+/// Credits `amount` to the claimable balance of `recipient` and returns the new balance.
+pub fn add_claimable(e: &Env, recipient: &Address, token: &Address, amount: u128) -> u128 {
+    let balance = read_claimable(e, recipient, token);
+    if balance == 0 {
+        index_claimable(e, recipient, token);
+    }
+
+    let key = DataKey::Claimable(recipient.clone(), token.clone());
+    e.storage().persistent().set(&key, &(balance + amount));
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    balance + amount
+}
+
+pub fn remove_claimable(e: &Env, recipient: &Address, token: &Address) {
+    let key = DataKey::Claimable(recipient.clone(), token.clone());
+    e.storage().persistent().remove(&key);
+
+    let id_key = DataKey::ClaimableId(recipient.clone(), token.clone());
+    let id: Option<u64> = e.storage().persistent().get(&id_key);
+    if let Some(id) = id {
+        e.storage().persistent().remove(&id_key);
+        e.storage().persistent().remove(&DataKey::ClaimableEntry(id));
+    }
+}
+
+/// Returns the claimables with ids from `start` up to, but excluding,
+/// `start + limit`. Ids of claimed balances are skipped.
+pub fn read_claimables(e: &Env, start: u64, limit: u32) -> Vec<Claimable> {
+    let mut claimables = Vec::new(e);
+    let end = start
+        .saturating_add(limit as u64)
+        .min(read_last_claimable_id(e) + 1);
+    for id in start.max(1)..end {
+        let entry: Option<(Address, Address)> =
+            e.storage().persistent().get(&DataKey::ClaimableEntry(id));
+        if let Some((recipient, token)) = entry {
+            let amount = read_claimable(e, &recipient, &token);
+            claimables.push_back(Claimable {
+                recipient,
+                token,
+                amount,
+            });
+        }
+    }
+    claimables
+}
+
+pub fn read_pause_state(e: &Env, token: Option<Address>) -> PauseState {
+    let state: Option<PauseState> = match token {
+        Some(token) => e.storage().persistent().get(&DataKey::TokenPause(token)),
+        None => e.storage().instance().get(&DataKey::GlobalPause),
+    };
+
+    state.unwrap_or(PauseState {
+        deposits: false,
+        withdrawals: false,
+    })
+}
+
+pub fn write_pause_state(e: &Env, token: Option<Address>, state: &PauseState) {
+    match token {
+        Some(token) => e.storage().persistent().set(&DataKey::TokenPause(token), state),
+        None => e.storage().instance().set(&DataKey::GlobalPause, state),
+    }
+}
+
+pub fn read_deposit_fee(e: &Env, token: Address) -> Option<DepositFee> {
+    e.storage().persistent().get(&DataKey::DepositFee(token))
+}
+
+pub fn write_deposit_fee(e: &Env, token: Address, fee: &DepositFee) {
+    let key = DataKey::DepositFee(token);
+    e.storage().persistent().set(&key, fee);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_deposit_fee(e: &Env, token: Address) {
+    e.storage().persistent().remove(&DataKey::DepositFee(token));
+}
+
+pub fn read_deposit_limit(e: &Env, token: &Address) -> Option<DepositLimit> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::DepositLimit(token.clone()))
+}
+
+pub fn write_deposit_limit(e: &Env, token: &Address, limit: &DepositLimit) {
+    let key = DataKey::DepositLimit(token.clone());
+    e.storage().persistent().set(&key, limit);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_deposit_limit(e: &Env, token: &Address) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::DepositLimit(token.clone()));
+}
+
+pub fn read_reserve_flow(e: &Env, token: &Address) -> ReserveFlow {
+    e.storage()
+        .persistent()
+        .get(&DataKey::ReserveFlow(token.clone()))
+        .unwrap_or(ReserveFlow {
+            deposited: 0,
+            withdrawn: 0,
+        })
+}
+
+pub fn write_reserve_flow(e: &Env, token: &Address, flow: &ReserveFlow) {
+    let key = DataKey::ReserveFlow(token.clone());
+    e.storage().persistent().set(&key, flow);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_reserve_flow(e: &Env, token: &Address) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::ReserveFlow(token.clone()));
+}
+
+pub fn read_token_stats(e: &Env, token: &Address) -> TokenStats {
+    e.storage()
+        .persistent()
+        .get(&DataKey::TokenStats(token.clone()))
+        .unwrap_or(TokenStats {
+            deposited: 0,
+            withdrawn: 0,
+            refunded: 0,
+            pending: 0,
+            deposit_count: 0,
+            withdrawal_count: 0,
+            refund_count: 0,
+            last_deposit: 0,
+            last_withdrawal: 0,
+            last_refund: 0,
+        })
+}
+
+pub fn write_token_stats(e: &Env, token: &Address, stats: &TokenStats) {
+    let key = DataKey::TokenStats(token.clone());
+    e.storage().persistent().set(&key, stats);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn read_rate_limit_groups(e: &Env) -> Vec<Symbol> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::RateLimitGroups)
+        .unwrap_or(Vec::new(e))
+}
+
+fn write_rate_limit_groups(e: &Env, groups: &Vec<Symbol>) {
+    let key = DataKey::RateLimitGroups;
+    e.storage().persistent().set(&key, groups);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn read_rate_limit_group(e: &Env, group: &Symbol) -> Option<RateLimitGroup> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::RateLimitGroup(group.clone()))
+}
+
+pub fn write_rate_limit_group(e: &Env, group: &Symbol, data: &RateLimitGroup) {
+    let mut groups = read_rate_limit_groups(e);
+    if !groups.contains(group) {
+        groups.push_back(group.clone());
+        write_rate_limit_groups(e, &groups);
+    }
+
+    let key = DataKey::RateLimitGroup(group.clone());
+    e.storage().persistent().set(&key, data);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_rate_limit_group(e: &Env, group: &Symbol) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::RateLimitGroup(group.clone()));
+
+    let mut groups = read_rate_limit_groups(e);
+    if let Some(index) = groups.first_index_of(group) {
+        groups.remove(index);
+        write_rate_limit_groups(e, &groups);
+    }
+}
+
+pub fn read_token_rate_limit_group(e: &Env, token: &Address) -> Option<Symbol> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::TokenRateLimitGroup(token.clone()))
+}
+
+pub fn write_token_rate_limit_group(e: &Env, token: &Address, group: &Symbol) {
+    let key = DataKey::TokenRateLimitGroup(token.clone());
+    e.storage().persistent().set(&key, group);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_token_rate_limit_group(e: &Env, token: &Address) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::TokenRateLimitGroup(token.clone()));
+}
+
+pub fn read_approval_threshold(e: &Env, token: &Address) -> Option<u128> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::ApprovalThreshold(token.clone()))
+}
+
+pub fn write_approval_threshold(e: &Env, token: &Address, threshold: u128) {
+    let key = DataKey::ApprovalThreshold(token.clone());
+    e.storage().persistent().set(&key, &threshold);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_approval_threshold(e: &Env, token: &Address) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::ApprovalThreshold(token.clone()));
+}
+
+pub fn read_recipient_limit(e: &Env, token: &Address) -> Option<RecipientLimit> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::RecipientLimit(token.clone()))
+}
+
+pub fn write_recipient_limit(e: &Env, token: &Address, limit: &RecipientLimit) {
+    let key = DataKey::RecipientLimit(token.clone());
+    e.storage().persistent().set(&key, limit);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_recipient_limit(e: &Env, token: &Address) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::RecipientLimit(token.clone()));
+}
+
+pub fn read_recipient_usage(
+    e: &Env,
+    recipient: &Address,
+    token: &Address,
+) -> Option<RecipientUsage> {
+    e.storage()
+        .temporary()
+        .get(&DataKey::RecipientUsage(recipient.clone(), token.clone()))
+}
+
+/// Usage is kept in temporary storage for as long as it affects the limits of
+/// the recipient, `live_for` seconds, after which it may expire.
+pub fn write_recipient_usage(
+    e: &Env,
+    recipient: &Address,
+    token: &Address,
+    usage: &RecipientUsage,
+    live_for: u64,
+) {
+    let key = DataKey::RecipientUsage(recipient.clone(), token.clone());
+    let ledgers = (live_for / LEDGER_CLOSE_TIME + 1).min(e.storage().max_ttl() as u64) as u32;
+    e.storage().temporary().set(&key, usage);
+    e.storage().temporary().extend_ttl(&key, ledgers, ledgers);
+}
+
+pub fn read_exempt_recipients(e: &Env) -> Vec<Address> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::ExemptRecipients)
+        .unwrap_or(Vec::new(e))
+}
+
+pub fn write_exempt_recipients(e: &Env, recipients: &Vec<Address>) {
+    let key = DataKey::ExemptRecipients;
+    e.storage().persistent().set(&key, recipients);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn read_token_decimals(e: &Env, token: &Address) -> Option<TokenDecimals> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::TokenDecimals(token.clone()))
+}
+
+pub fn write_token_decimals(e: &Env, token: &Address, decimals: &TokenDecimals) {
+    let key = DataKey::TokenDecimals(token.clone());
+    e.storage().persistent().set(&key, decimals);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_token_decimals(e: &Env, token: &Address) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::TokenDecimals(token.clone()));
+}
+
+pub fn read_withdrawal_delay(e: &Env) -> u64 {
+    let key = DataKey::WithdrawalDelay;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(DEFAULT_WITHDRAWAL_DELAY)
+}
+
+pub fn write_withdrawal_delay(e: &Env, delay: u64) {
+    let key = DataKey::WithdrawalDelay;
+    e.storage().instance().set(&key, &delay);
+}
+
+pub fn read_upgrade_delay(e: &Env) -> u64 {
+    let key = DataKey::UpgradeDelay;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(DEFAULT_UPGRADE_DELAY)
+}
+
+pub fn write_upgrade_delay(e: &Env, delay: u64) {
+    let key = DataKey::UpgradeDelay;
+    e.storage().instance().set(&key, &delay);
+}
+
+pub fn read_scheduled_upgrade(e: &Env) -> Option<ScheduledUpgrade> {
+    e.storage().instance().get(&DataKey::ScheduledUpgrade)
+}
+
+pub fn write_scheduled_upgrade(e: &Env, upgrade: &ScheduledUpgrade) {
+    e.storage().instance().set(&DataKey::ScheduledUpgrade, upgrade);
+}
+
+pub fn remove_scheduled_upgrade(e: &Env) {
+    e.storage().instance().remove(&DataKey::ScheduledUpgrade);
+}
+
+pub fn write_pending_withdrawal(e: &Env, withdrawal: &PendingWithdrawal) -> u64 {
+    let id: u64 = e
+        .storage()
+        .instance()
+        .get(&DataKey::LastWithdrawalId)
+        .unwrap_or(0)
+        + 1;
+    e.storage().instance().set(&DataKey::LastWithdrawalId, &id);
+
+    let key = DataKey::PendingWithdrawal(id);
+    e.storage().persistent().set(&key, withdrawal);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    id
+}
+
+/// Reads a queued withdrawal and extends its TTL, queued withdrawals are not
+/// covered by `extent_ttl` since there is no bound on how many there are.
+pub fn read_pending_withdrawal(e: &Env, id: u64) -> Result<PendingWithdrawal, ContractError> {
+    let key = DataKey::PendingWithdrawal(id);
+    let withdrawal = e
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::WithdrawalNotFound)?;
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    Ok(withdrawal)
+}
+
+pub fn update_pending_withdrawal(e: &Env, id: u64, withdrawal: &PendingWithdrawal) {
+    let key = DataKey::PendingWithdrawal(id);
+    e.storage().persistent().set(&key, withdrawal);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_pending_withdrawal(e: &Env, id: u64) {
+    let key = DataKey::PendingWithdrawal(id);
+    e.storage().persistent().remove(&key);
+}
+
+pub fn read_withdrawal_approvals(e: &Env, id: u64) -> Option<Vec<Address>> {
+    let key = DataKey::WithdrawalApprovals(id);
+    let approvals = e.storage().persistent().get(&key)?;
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    Some(approvals)
+}
+
+pub fn write_withdrawal_approvals(e: &Env, id: u64, approvals: &Vec<Address>) {
+    let key = DataKey::WithdrawalApprovals(id);
+    e.storage().persistent().set(&key, approvals);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_withdrawal_approvals(e: &Env, id: u64) {
+    e.storage()
+        .persistent()
+        .remove(&DataKey::WithdrawalApprovals(id));
+}
+
 pub fn write_token_data(env: &Env, token_address: Address, data: TokenData) {
     let key = DataKey::TokenData(token_address);
     env.storage().persistent().set(&key, &data);
 }
 
+pub fn remove_token_data(env: &Env, token_address: Address) {
+    let key = DataKey::TokenData(token_address);
+    env.storage().persistent().remove(&key);
+}
+
 pub fn set_xcall_network_address(e: &Env, value: String) {
     e.storage().instance().set(&DataKey::XCallNetworkAddress, &value);
 }
@@ -74,6 +620,15 @@ pub fn write_tokens(e: &Env, token: Address) {
     e.storage().persistent().set(&key, &tokens);
 }
 
+pub fn remove_token(e: &Env, token: Address) {
+    let key = DataKey::Tokens;
+    let mut tokens = read_tokens(e);
+    if let Some(index) = tokens.first_index_of(&token) {
+        tokens.remove(index);
+        e.storage().persistent().set(&key, &tokens);
+    }
+}
+
 pub fn read_tokens(e: &Env) -> Vec<Address> {
     let key = DataKey::Tokens;
     let tokens: Vec<Address> = match e.storage().persistent().get(&key) {
@@ -103,5 +658,108 @@ pub fn extent_ttl(e: &Env) {
             INSTANCE_BUMP_AMOUNT,
         );
 
+        let pause_key = DataKey::TokenPause(token.clone());
+        if e.storage().persistent().has(&pause_key) {
+            e.storage().persistent().extend_ttl(
+                &pause_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let fee_key = DataKey::DepositFee(token.clone());
+        if e.storage().persistent().has(&fee_key) {
+            e.storage().persistent().extend_ttl(
+                &fee_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let decimals_key = DataKey::TokenDecimals(token.clone());
+        if e.storage().persistent().has(&decimals_key) {
+            e.storage().persistent().extend_ttl(
+                &decimals_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let deposit_limit_key = DataKey::DepositLimit(token.clone());
+        if e.storage().persistent().has(&deposit_limit_key) {
+            e.storage().persistent().extend_ttl(
+                &deposit_limit_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let group_key = DataKey::TokenRateLimitGroup(token.clone());
+        if e.storage().persistent().has(&group_key) {
+            e.storage().persistent().extend_ttl(
+                &group_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let threshold_key = DataKey::ApprovalThreshold(token.clone());
+        if e.storage().persistent().has(&threshold_key) {
+            e.storage().persistent().extend_ttl(
+                &threshold_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let recipient_limit_key = DataKey::RecipientLimit(token.clone());
+        if e.storage().persistent().has(&recipient_limit_key) {
+            e.storage().persistent().extend_ttl(
+                &recipient_limit_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let reserve_flow_key = DataKey::ReserveFlow(token.clone());
+        if e.storage().persistent().has(&reserve_flow_key) {
+            e.storage().persistent().extend_ttl(
+                &reserve_flow_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+
+        let stats_key = DataKey::TokenStats(token);
+        if e.storage().persistent().has(&stats_key) {
+            e.storage().persistent().extend_ttl(
+                &stats_key,
+                INSTANCE_LIFETIME_THRESHOLD,
+                INSTANCE_BUMP_AMOUNT,
+            );
+        }
+    }
+
+    if e.storage().persistent().has(&DataKey::ExemptRecipients) {
+        e.storage().persistent().extend_ttl(
+            &DataKey::ExemptRecipients,
+            INSTANCE_LIFETIME_THRESHOLD,
+            INSTANCE_BUMP_AMOUNT,
+        );
+    }
+
+    if e.storage().persistent().has(&DataKey::RateLimitGroups) {
+        e.storage().persistent().extend_ttl(
+            &DataKey::RateLimitGroups,
+            INSTANCE_LIFETIME_THRESHOLD,
+            INSTANCE_BUMP_AMOUNT,
+        );
+    }
+    for group in read_rate_limit_groups(e) {
+        e.storage().persistent().extend_ttl(
+            &DataKey::RateLimitGroup(group),
+            INSTANCE_LIFETIME_THRESHOLD,
+            INSTANCE_BUMP_AMOUNT,
+        );
     }
 }
@@ -1,6 +1,10 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, BytesN, Symbol, Vec};
 
 pub(crate) const POINTS: u128 = 10000;
+pub(crate) const DEFAULT_WITHDRAWAL_DELAY: u64 = 86400;
+pub(crate) const PROPOSAL_EXPIRY: u64 = 7 * 86400;
+pub(crate) const DEFAULT_UPGRADE_DELAY: u64 = 2 * 86400;
+pub(crate) const MIN_UPGRADE_DELAY: u64 = 3600;
 
 #[derive(Clone)]
 #[contracttype]
@@ -10,7 +14,38 @@ pub enum DataKey {
     Config,
     Tokens,
     TokenData(Address),
-    XCallNetworkAddress
+    XCallNetworkAddress,
+    Guardian,
+    GlobalPause,
+    TokenPause(Address),
+    LastWithdrawalId,
+    PendingWithdrawal(u64),
+    WithdrawalDelay,
+    PendingAdmin,
+    PendingUpgradeAuthority,
+    ScheduledUpgrade,
+    UpgradeDelay,
+    SchemaVersion,
+    DepositFee(Address),
+    Rescuer,
+    Claimable(Address, Address),
+    Claimables,
+    LastClaimableId,
+    ClaimableId(Address, Address),
+    ClaimableEntry(u64),
+    TokenDecimals(Address),
+    DepositLimit(Address),
+    RateLimitGroup(Symbol),
+    RateLimitGroups,
+    TokenRateLimitGroup(Address),
+    ApprovalConfig,
+    ApprovalThreshold(Address),
+    WithdrawalApprovals(u64),
+    RecipientLimit(Address),
+    RecipientUsage(Address, Address),
+    ExemptRecipients,
+    ReserveFlow(Address),
+    TokenStats(Address)
 }
 
 #[derive(Clone)]
@@ -20,4 +55,192 @@ pub struct TokenData {
     pub percentage: u32,
     pub last_update: u64,
     pub current_limit: u64,
+    pub max_withdraw: u128,
+    pub withdrawn: u128,
+    pub windows: Vec<RateLimitWindow>,
+}
+
+/// An additional rate limit window enforced alongside the primary one in
+/// `TokenData`. Each window decays its own reserve over its own `period`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RateLimitWindow {
+    pub period: u64,
+    pub percentage: u32,
+    pub last_update: u64,
+    pub current_limit: u64,
+}
+
+/// Rate limit shared by correlated tokens. `limit` is computed over the
+/// combined balance of `tokens` and every withdrawal of a member counts
+/// against it. Members are expected to use the same decimals.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RateLimitGroup {
+    pub tokens: Vec<Address>,
+    pub limit: RateLimitWindow,
+}
+
+/// Reserve currently held back by a rate limit window and the amount it
+/// still allows to be withdrawn.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct WindowCapacity {
+    pub period: u64,
+    pub percentage: u32,
+    pub reserve: u128,
+    pub available: u128,
+}
+
+/// Caps the amount of a token deposited per rolling `period`. `deposited`
+/// decays linearly by `max_deposit` per `period` since `last_update`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DepositLimit {
+    pub period: u64,
+    pub max_deposit: u128,
+    pub deposited: u128,
+    pub last_update: u64,
+}
+
+/// Result of `simulate_withdraw`. `reserve` is the amount that has to stay in
+/// the contract now and `withdrawable_at` is `None` if the requested amount
+/// can't be withdrawn at the current balance. `paused`, `requires_approval`
+/// and `recipient_blocked` report the gates that would hold the withdrawal
+/// back besides the rate limits, `withdrawable_at` is also `None` when one of
+/// them is set.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct WithdrawSimulation {
+    pub allowed: bool,
+    pub reserve: u128,
+    pub max_withdrawable: u128,
+    pub withdrawable_at: Option<u64>,
+    pub paused: bool,
+    pub requires_approval: bool,
+    pub recipient_blocked: bool,
+}
+
+/// Limits on withdrawals of a token to a single recipient: at most
+/// `max_amount` per rolling `period` and `cooldown` seconds between two
+/// withdrawals. A `max_amount` or `cooldown` of 0 disables that limit.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RecipientLimit {
+    pub period: u64,
+    pub max_amount: u128,
+    pub cooldown: u64,
+}
+
+/// Withdrawals of a token to a recipient, `withdrawn` decays linearly by
+/// `max_amount` per `period` since `last_withdrawal`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RecipientUsage {
+    pub withdrawn: u128,
+    pub last_withdrawal: u64,
+}
+
+/// Amounts of a token that entered and left the contract since its reserves
+/// were last reported to the ICON hub.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ReserveFlow {
+    pub deposited: u128,
+    pub withdrawn: u128,
+}
+
+/// Lifetime accounting of a token. `withdrawn` and `refunded` are the amounts
+/// paid out for `WithdrawTo` and `DepositRevert` messages, of which `pending`
+/// is still held by the contract in claimables. Queued withdrawals are only
+/// counted once they are paid out or escrowed.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct TokenStats {
+    pub deposited: u128,
+    pub withdrawn: u128,
+    pub refunded: u128,
+    pub pending: u128,
+    pub deposit_count: u64,
+    pub withdrawal_count: u64,
+    pub refund_count: u64,
+    pub last_deposit: u64,
+    pub last_withdrawal: u64,
+    pub last_refund: u64,
+}
+
+/// Result of `reconcile`, the balance of a token against the balance expected
+/// from its `TokenStats`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Reconciliation {
+    pub balance: u128,
+    pub expected: u128,
+    pub surplus: u128,
+    pub shortfall: u128,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PauseState {
+    pub deposits: bool,
+    pub withdrawals: bool,
+}
+
+/// A queued withdrawal. `refund` is set for refunds of reverted deposits,
+/// which are not subject to recipient limits.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingWithdrawal {
+    pub token: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub unlock_time: u64,
+    pub refund: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingAddress {
+    pub address: Address,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ScheduledUpgrade {
+    pub wasm_hash: BytesN<32>,
+    pub executable_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum FeeType {
+    BasisPoints(u32),
+    Fixed(u128),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DepositFee {
+    pub fee_type: FeeType,
+    pub minimum: u128,
+}
+
+/// Decimals of a token on Stellar and on the ICON hub, amounts in xCall
+/// messages are scaled from one to the other.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct TokenDecimals {
+    pub local: u32,
+    pub hub: u32,
+}
+
+/// Tokens owed to `recipient` after a payout to it failed.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Claimable {
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: u128,
 }
@@ -1,13 +1,31 @@
 #![cfg(test)]
 extern crate std;
 
-use crate::{config, contract::AssetManagerClient, storage_types::DataKey};
+use crate::{
+    config,
+    contract::AssetManagerClient,
+    errors::ContractError,
+    events::{DepositEvent, WithdrawEvent, EVENT_VERSION},
+    migration::SCHEMA_VERSION,
+    states::{DAY_IN_LEDGERS, INSTANCE_BUMP_AMOUNT},
+    storage_types::{
+        Claimable, DataKey, DepositFee, DepositLimit, FeeType, PauseState, PendingAddress,
+        PendingWithdrawal, RateLimitGroup, RateLimitWindow, RecipientLimit, Reconciliation,
+        ReserveFlow, ScheduledUpgrade, TokenDecimals, TokenStats, WindowCapacity,
+        WithdrawSimulation, DEFAULT_UPGRADE_DELAY, PROPOSAL_EXPIRY,
+    },
+};
 use soroban_sdk::{
-    testutils::{storage::Persistent, Address as _, AuthorizedFunction, AuthorizedInvocation},
-    token, Address, Bytes, IntoVal, String, Symbol, Vec,
+    testutils::{
+        storage::Persistent, Address as _, AuthorizedFunction, AuthorizedInvocation, Events, Ledger,
+    },
+    token, vec, Address, Bytes, IntoVal, String, Symbol, TryFromVal, Vec,
 };
 
-use soroban_rlp::balanced::messages::{deposit_revert::DepositRevert, withdraw_to::WithdrawTo};
+use soroban_rlp::balanced::messages::{
+    deposit_batch::DepositBatch, deposit_batch_revert::DepositBatchRevert,
+    deposit_revert::DepositRevert, reserve_report::ReserveReport, withdraw_to::WithdrawTo,
+};
 
 use super::setup::*;
 
@@ -22,13 +40,13 @@ fn test_initialize() {
 }
 
 #[test]
-fn test_set_admin() {
+fn test_propose_and_accept_admin() {
     let ctx = TestContext::default();
     let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
     ctx.init_context(&client);
 
     let new_admin: Address = Address::generate(&ctx.env);
-    client.set_admin(&new_admin);
+    client.propose_admin(&new_admin);
 
     assert_eq!(
         ctx.env.auths(),
@@ -37,14 +55,81 @@ fn test_set_admin() {
             AuthorizedInvocation {
                 function: AuthorizedFunction::Contract((
                     ctx.registry.clone(),
-                    Symbol::new(&ctx.env, "set_admin"),
+                    Symbol::new(&ctx.env, "propose_admin"),
                     (&new_admin,).into_val(&ctx.env)
                 )),
                 sub_invocations: std::vec![]
             }
         )]
     );
+    assert_eq!(client.get_admin(), ctx.admin);
+    assert_eq!(
+        client.get_pending_admin(),
+        Some(PendingAddress {
+            address: new_admin.clone(),
+            expires_at: ctx.env.ledger().timestamp() + PROPOSAL_EXPIRY
+        })
+    );
+
+    client.accept_admin();
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            new_admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "accept_admin"),
+                    ().into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
     assert_eq!(client.get_admin(), new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+}
+
+#[test]
+fn test_accept_admin_after_expiry() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let new_admin: Address = Address::generate(&ctx.env);
+    client.propose_admin(&new_admin);
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PROPOSAL_EXPIRY + 1);
+
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(ContractError::ProposalExpired))
+    );
+    assert_eq!(client.get_admin(), ctx.admin);
+}
+
+#[test]
+fn test_cancel_admin_proposal() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    assert_eq!(
+        client.try_cancel_admin_proposal(),
+        Err(Ok(ContractError::NoPendingProposal))
+    );
+
+    let new_admin: Address = Address::generate(&ctx.env);
+    client.propose_admin(&new_admin);
+    client.cancel_admin_proposal();
+
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(ContractError::NoPendingProposal))
+    );
+    assert_eq!(client.get_admin(), ctx.admin);
 }
 
 #[test]
@@ -506,13 +591,13 @@ fn test_reset_limit() {
 }
 
 #[test]
-fn test_set_upgrade_authority() {
+fn test_propose_and_accept_upgrade_authority() {
     let ctx = TestContext::default();
     let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
     ctx.init_context(&client);
 
     let new_upgrade_authority = Address::generate(&ctx.env);
-    client.set_upgrade_authority(&new_upgrade_authority);
+    client.propose_upgrade_authority(&new_upgrade_authority);
 
     assert_eq!(
         ctx.env.auths(),
@@ -521,16 +606,2663 @@ fn test_set_upgrade_authority() {
             AuthorizedInvocation {
                 function: AuthorizedFunction::Contract((
                     ctx.registry.clone(),
-                    Symbol::new(&ctx.env, "set_upgrade_authority"),
+                    Symbol::new(&ctx.env, "propose_upgrade_authority"),
                     (&new_upgrade_authority,).into_val(&ctx.env)
                 )),
                 sub_invocations: std::vec![]
             }
         )]
     );
+    assert_eq!(
+        client.get_pending_upgrade_authority(),
+        Some(PendingAddress {
+            address: new_upgrade_authority.clone(),
+            expires_at: ctx.env.ledger().timestamp() + PROPOSAL_EXPIRY
+        })
+    );
+
+    client.accept_upgrade_authority();
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            new_upgrade_authority.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "accept_upgrade_authority"),
+                    ().into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
 
     ctx.env.as_contract(&client.address, || {
         let config = config::get_config(&ctx.env);
         assert_eq!(config.upgrade_authority, new_upgrade_authority)
     });
+    assert_eq!(client.get_pending_upgrade_authority(), None);
+}
+
+#[test]
+fn test_cancel_upgrade_authority_proposal() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let new_upgrade_authority = Address::generate(&ctx.env);
+    client.propose_upgrade_authority(&new_upgrade_authority);
+    client.cancel_upgrade_auth_proposal();
+
+    assert_eq!(client.get_pending_upgrade_authority(), None);
+    assert_eq!(
+        client.try_accept_upgrade_authority(),
+        Err(Ok(ContractError::NoPendingProposal))
+    );
+
+    client.propose_upgrade_authority(&new_upgrade_authority);
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PROPOSAL_EXPIRY + 1);
+    assert_eq!(
+        client.try_accept_upgrade_authority(),
+        Err(Ok(ContractError::ProposalExpired))
+    );
+}
+
+#[test]
+fn test_set_guardian() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let guardian = Address::generate(&ctx.env);
+    client.set_guardian(&guardian);
+
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            ctx.admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "set_guardian"),
+                    (&guardian,).into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(client.get_guardian(), guardian);
+}
+
+#[test]
+fn test_pause_and_unpause() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let guardian = Address::generate(&ctx.env);
+    client.set_guardian(&guardian);
+
+    client.pause(&Some(ctx.token.clone()), &true, &false);
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            guardian.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "pause"),
+                    (Some(ctx.token.clone()), true, false).into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert!(client.is_deposit_paused(&ctx.token));
+    assert!(!client.is_withdraw_paused(&ctx.token));
+    assert!(!client.is_deposit_paused(&ctx.native_token));
+
+    client.pause(&None, &false, &true);
+    assert!(client.is_withdraw_paused(&ctx.token));
+    assert!(client.is_withdraw_paused(&ctx.native_token));
+    assert_eq!(
+        client.get_pause_state(&None),
+        PauseState {
+            deposits: false,
+            withdrawals: true
+        }
+    );
+
+    client.unpause(&None, &true, &true);
+    client.unpause(&Some(ctx.token.clone()), &true, &false);
+    assert!(!client.is_deposit_paused(&ctx.token));
+    assert!(!client.is_withdraw_paused(&ctx.token));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #17)")]
+fn test_deposit_panic_when_paused() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    client.set_guardian(&Address::generate(&ctx.env));
+    client.pause(&None, &true, &false);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    ctx.mint_native_token(&ctx.depositor, 500);
+
+    client.deposit(
+        &ctx.depositor,
+        &ctx.token,
+        &100000,
+        &Option::Some(String::from_str(&ctx.env, "")),
+        &Option::Some(Bytes::from_array(&ctx.env, &[0u8; 32])),
+    );
+}
+
+#[test]
+fn test_withdraw_retained_while_paused() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    client.set_guardian(&Address::generate(&ctx.env));
+    client.pause(&Some(ctx.token.clone()), &false, &true);
+
+    let bnusd_amount = 100000u128;
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &((bnusd_amount * 2) as i128));
+
+    let data = WithdrawTo::new(
+        ctx.token.to_string(),
+        ctx.withdrawer.to_string(),
+        bnusd_amount,
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    assert_eq!(token_client.balance(&ctx.withdrawer), 0);
+    assert_eq!(
+        client.get_pending_withdrawal(&1),
+        PendingWithdrawal {
+            token: ctx.token.clone(),
+            to: ctx.withdrawer.clone(),
+            amount: bnusd_amount,
+            unlock_time: ctx.env.ledger().timestamp(),
+            refund: false,
+        }
+    );
+    assert_eq!(
+        client.try_claim_withdrawal(&1),
+        Err(Ok(ContractError::Paused))
+    );
+
+    client.unpause(&Some(ctx.token.clone()), &false, &true);
+    client.claim_withdrawal(&1);
+
+    assert_eq!(token_client.balance(&ctx.withdrawer), bnusd_amount as i128);
+    assert_eq!(
+        client.try_get_pending_withdrawal(&1),
+        Err(Ok(ContractError::WithdrawalNotFound))
+    );
+}
+
+#[test]
+fn test_update_rate_limit() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 3000);
+
+    client.update_rate_limit(&ctx.token, &600, &1000);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "rate_limit_updated"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                (600u64, 1000u32).into_val(&ctx.env)
+            )
+        ]
+    );
+
+    let (period, percentage, _, current_limit) = client.get_rate_limit(&ctx.token);
+    assert_eq!(period, 600);
+    assert_eq!(percentage, 1000);
+    assert_eq!(current_limit, 10000);
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 10000);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #14)")]
+fn test_update_rate_limit_panic_for_unknown_token() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    client.update_rate_limit(&ctx.token, &600, &1000);
+}
+
+#[test]
+fn test_remove_rate_limit() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    client.remove_rate_limit(&ctx.token);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "rate_limit_removed"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                ().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(
+        client.try_get_rate_limit(&ctx.token),
+        Err(Ok(ContractError::TokenDoesNotExists))
+    );
+
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    assert_eq!(client.get_rate_limit(&ctx.token).1, 300);
+}
+
+#[test]
+fn test_withdraw_cap() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &1000000);
+
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    client.set_withdraw_cap(&ctx.token, &50000);
+    assert_eq!(client.get_withdraw_cap(&ctx.token), (50000, 0));
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 950000);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 30000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 30000);
+    assert_eq!(client.get_withdraw_cap(&ctx.token), (50000, 30000));
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 950000);
+
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 30000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 30000);
+    assert_eq!(client.get_pending_withdrawal(&1).amount, 30000);
+
+    // Half a period releases half of the cap.
+    ctx.env.ledger().with_mut(|li| li.timestamp += 150);
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 970000 - 45000);
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 60000);
+    assert_eq!(client.get_withdraw_cap(&ctx.token), (50000, 35000));
+
+    // The cap is released over the rate limit period, which can't be 0.
+    assert_eq!(
+        client.try_update_rate_limit(&ctx.token, &0, &300),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    client.set_withdraw_cap(&ctx.token, &0);
+    client.update_rate_limit(&ctx.token, &0, &300);
+    assert_eq!(
+        client.try_set_withdraw_cap(&ctx.token, &50000),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_withdraw_queued_when_exceeding_limit() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &9000);
+    client.set_withdrawal_delay(&600);
+
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 50000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    let unlock_time = ctx.env.ledger().timestamp() + 600;
+    let withdrawal = PendingWithdrawal {
+        token: ctx.token.clone(),
+        to: ctx.withdrawer.clone(),
+        amount: 50000,
+        unlock_time,
+        refund: false,
+    };
+    assert_eq!(client.get_pending_withdrawal(&1), withdrawal);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "withdrawal_queued"),
+                    EVENT_VERSION,
+                    1u64
+                )
+                    .into_val(&ctx.env),
+                withdrawal.into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(token_client.balance(&ctx.withdrawer), 0);
+    assert_eq!(
+        client.try_claim_withdrawal(&1),
+        Err(Ok(ContractError::WithdrawalLocked))
+    );
+
+    ctx.env.ledger().with_mut(|li| li.timestamp = unlock_time);
+    assert_eq!(
+        client.try_claim_withdrawal(&1),
+        Err(Ok(ContractError::ExceedsWithdrawLimit))
+    );
+
+    client.update_rate_limit(&ctx.token, &300, &4000);
+    ctx.env.ledger().with_mut(|li| li.timestamp += 300);
+    client.claim_withdrawal(&1);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 50000);
+}
+
+#[test]
+fn test_cancel_withdrawal() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &9000);
+    let guardian = Address::generate(&ctx.env);
+    client.set_guardian(&guardian);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    for _ in 0..2 {
+        let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 50000)
+            .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+        client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    }
+
+    assert_eq!(
+        client.try_cancel_withdrawal(&ctx.withdrawer, &1),
+        Err(Ok(ContractError::OnlyAdminOrGuardian))
+    );
+    client.cancel_withdrawal(&guardian, &1);
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            guardian.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "cancel_withdrawal"),
+                    (&guardian, 1u64).into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        client.try_get_pending_withdrawal(&1),
+        Err(Ok(ContractError::WithdrawalNotFound))
+    );
+
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += client.get_withdrawal_delay());
+    assert_eq!(
+        client.try_cancel_withdrawal(&ctx.admin, &2),
+        Err(Ok(ContractError::WithdrawalUnlocked))
+    );
+}
+
+#[test]
+fn test_deposit_event() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    ctx.mint_native_token(&ctx.depositor, 500);
+
+    let to = String::from_str(&ctx.env, "icon01/hxjkdvhui");
+    client.deposit(
+        &ctx.depositor,
+        &ctx.token,
+        &100000,
+        &Option::Some(to.clone()),
+        &Option::None,
+    );
+
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "deposit"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                DepositEvent {
+                    from: ctx.depositor.clone(),
+                    to,
+                    amount: 100000,
+                    fee: 0,
+                    sn: 1,
+                }
+                .into_val(&ctx.env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_withdraw_to_events() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &300);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &200000);
+
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 100000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    let event = WithdrawEvent {
+        to: ctx.withdrawer.clone(),
+        amount: 100000,
+    };
+    let events = ctx.contract_events();
+    assert_eq!(
+        events.slice(events.len() - 2..),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "withdraw_to"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                event.clone().into_val(&ctx.env)
+            ),
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "withdrawn"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                event.into_val(&ctx.env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_admin_events() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let new_admin = Address::generate(&ctx.env);
+    client.propose_admin(&new_admin);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "admin_proposed"), EVENT_VERSION).into_val(&ctx.env),
+                PendingAddress {
+                    address: new_admin.clone(),
+                    expires_at: ctx.env.ledger().timestamp() + PROPOSAL_EXPIRY
+                }
+                .into_val(&ctx.env)
+            )
+        ]
+    );
+    client.accept_admin();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "admin_changed"), EVENT_VERSION).into_val(&ctx.env),
+                new_admin.into_val(&ctx.env)
+            )
+        ]
+    );
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &300);
+    client.reset_limit(&ctx.token);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "limit_reset"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                3000u64.into_val(&ctx.env)
+            )
+        ]
+    );
+
+    client.set_guardian(&new_admin);
+    client.pause(&None, &true, &false);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "paused"),
+                    EVENT_VERSION,
+                    Option::<Address>::None
+                )
+                    .into_val(&ctx.env),
+                PauseState {
+                    deposits: true,
+                    withdrawals: false
+                }
+                .into_val(&ctx.env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_schedule_and_execute_upgrade() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let wasm_hash = ctx.upload_xcall_manager_wasm();
+    client.schedule_upgrade(&wasm_hash);
+    let upgrade = ScheduledUpgrade {
+        wasm_hash: wasm_hash.clone(),
+        executable_at: ctx.env.ledger().timestamp() + DEFAULT_UPGRADE_DELAY,
+    };
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            ctx.upgrade_authority.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "schedule_upgrade"),
+                    (wasm_hash.clone(),).into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgrade_scheduled"), EVENT_VERSION).into_val(&ctx.env),
+                upgrade.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_scheduled_upgrade(), Some(upgrade));
+    assert_eq!(
+        client.try_schedule_upgrade(&wasm_hash),
+        Err(Ok(ContractError::UpgradeAlreadyScheduled))
+    );
+
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += DEFAULT_UPGRADE_DELAY - 1);
+    assert_eq!(
+        client.try_execute_upgrade(),
+        Err(Ok(ContractError::UpgradeLocked))
+    );
+
+    ctx.env.ledger().with_mut(|li| li.timestamp += 1);
+    client.execute_upgrade();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgraded"), EVENT_VERSION).into_val(&ctx.env),
+                wasm_hash.into_val(&ctx.env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_cancel_upgrade() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let guardian = Address::generate(&ctx.env);
+    client.set_guardian(&guardian);
+    assert_eq!(
+        client.try_set_upgrade_delay(&3599),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    client.set_upgrade_delay(&3600);
+    assert_eq!(client.get_upgrade_delay(), 3600);
+
+    let wasm_hash = ctx.upload_xcall_manager_wasm();
+    client.schedule_upgrade(&wasm_hash);
+    assert_eq!(
+        client.try_cancel_upgrade(&ctx.depositor),
+        Err(Ok(ContractError::OnlyAdminOrGuardian))
+    );
+
+    client.cancel_upgrade(&guardian);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgrade_cancelled"), EVENT_VERSION).into_val(&ctx.env),
+                (wasm_hash, guardian.clone()).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_scheduled_upgrade(), None);
+
+    ctx.env.ledger().with_mut(|li| li.timestamp += 3600);
+    assert_eq!(
+        client.try_execute_upgrade(),
+        Err(Ok(ContractError::UpgradeNotScheduled))
+    );
+    assert_eq!(
+        client.try_cancel_upgrade(&ctx.admin),
+        Err(Ok(ContractError::UpgradeNotScheduled))
+    );
+}
+
+#[test]
+fn test_version_after_initialize() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert_eq!(
+        client.try_migrate(),
+        Err(Ok(ContractError::AlreadyMigrated))
+    );
+}
+
+#[test]
+fn test_upgrade_and_migrate_previous_version() {
+    let ctx = TestContext::default();
+    let address = ctx.deploy_previous_version();
+    let previous = asset_manager_v0::Client::new(&ctx.env, &address);
+    previous.configure_rate_limit(&ctx.token, &300, &300);
+    previous.upgrade(&ctx.upload_asset_manager_wasm());
+
+    let client = AssetManagerClient::new(&ctx.env, &address);
+    assert_eq!(client.version(), 0);
+    assert!(client.try_get_rate_limit(&ctx.token).is_err());
+
+    client.migrate();
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            ctx.upgrade_authority.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    address.clone(),
+                    Symbol::new(&ctx.env, "migrate"),
+                    ().into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    let events = ctx.env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &ctx.env,
+            (
+                address.clone(),
+                (Symbol::new(&ctx.env, "migrated"), EVENT_VERSION).into_val(&ctx.env),
+                (0u32, SCHEMA_VERSION).into_val(&ctx.env)
+            )
+        ]
+    );
+
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert_eq!(client.get_admin(), ctx.admin);
+    assert_eq!(client.get_config().upgrade_authority, ctx.upgrade_authority);
+    assert_eq!(client.get_config().fee_collector, ctx.admin);
+    assert_eq!(client.get_rate_limit(&ctx.token), (300, 300, 0, 0));
+    assert_eq!(
+        client.try_migrate(),
+        Err(Ok(ContractError::AlreadyMigrated))
+    );
+}
+
+#[test]
+fn test_deposit_with_basis_points_fee() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let fee = DepositFee {
+        fee_type: FeeType::BasisPoints(100),
+        minimum: 0,
+    };
+    client.set_deposit_fee(&ctx.token, &fee);
+    assert_eq!(client.get_deposit_fee(&ctx.token), Some(fee.clone()));
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "deposit_fee_updated"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                fee.into_val(&ctx.env)
+            )
+        ]
+    );
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    ctx.mint_native_token(&ctx.depositor, 500);
+
+    let to = String::from_str(&ctx.env, "icon01/hxjkdvhui");
+    client.deposit(
+        &ctx.depositor,
+        &ctx.token,
+        &100000,
+        &Option::Some(to.clone()),
+        &Option::None,
+    );
+    assert_eq!(token_client.balance(&ctx.registry), 99000);
+    assert_eq!(token_client.balance(&ctx.fee_collector), 1000);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "deposit"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                DepositEvent {
+                    from: ctx.depositor.clone(),
+                    to,
+                    amount: 99000,
+                    fee: 1000,
+                    sn: 1,
+                }
+                .into_val(&ctx.env)
+            )
+        ]
+    );
+
+    let data = DepositRevert::new(ctx.token.clone(), ctx.depositor.clone(), 99000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "DepositRevert"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.xcall_client.get_network_address(), &data, &sources);
+    assert_eq!(token_client.balance(&ctx.depositor), 99000);
+    assert_eq!(token_client.balance(&ctx.registry), 0);
+}
+
+#[test]
+fn test_deposit_fee_fixed_and_minimum() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    assert_eq!(client.calculate_deposit_fee(&ctx.token, &10000), 0);
+
+    client.set_deposit_fee(
+        &ctx.token,
+        &DepositFee {
+            fee_type: FeeType::Fixed(500),
+            minimum: 0,
+        },
+    );
+    assert_eq!(client.calculate_deposit_fee(&ctx.token, &10000), 500);
+    assert_eq!(
+        client.try_calculate_deposit_fee(&ctx.token, &500),
+        Err(Ok(ContractError::AmountIsLessThanMinimumAmount))
+    );
+
+    client.set_deposit_fee(
+        &ctx.token,
+        &DepositFee {
+            fee_type: FeeType::BasisPoints(10),
+            minimum: 200,
+        },
+    );
+    assert_eq!(client.calculate_deposit_fee(&ctx.token, &10000), 200);
+    assert_eq!(client.calculate_deposit_fee(&ctx.token, &1000000), 1000);
+
+    assert_eq!(
+        client.try_set_deposit_fee(
+            &ctx.token,
+            &DepositFee {
+                fee_type: FeeType::BasisPoints(10001),
+                minimum: 0,
+            }
+        ),
+        Err(Ok(ContractError::PercentageShouldBeLessThanOrEqualToPOINTS))
+    );
+
+    client.remove_deposit_fee(&ctx.token);
+    assert_eq!(client.get_deposit_fee(&ctx.token), None);
+    assert_eq!(client.calculate_deposit_fee(&ctx.token, &10000), 0);
+    assert_eq!(
+        client.try_remove_deposit_fee(&ctx.token),
+        Err(Ok(ContractError::DepositFeeNotSet))
+    );
+}
+
+#[test]
+fn test_quote_deposit_fee() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let hub_nid = String::from_str(&ctx.env, "icon01");
+    ctx.set_connection_fee(&hub_nid, 150, 50);
+
+    let sources = Some(Vec::from_array(
+        &ctx.env,
+        [ctx.centralized_connection.to_string()],
+    ));
+    let with_rollback = ctx.xcall_client.get_fee(&hub_nid, &true, &sources);
+    let without_rollback = ctx.xcall_client.get_fee(&hub_nid, &false, &sources);
+    assert_eq!(with_rollback, 100 + 150 + 50);
+    assert_eq!(without_rollback, 100 + 150);
+    assert_eq!(client.quote_deposit_fee(&true), with_rollback);
+    assert_eq!(client.quote_deposit_fee(&false), without_rollback);
+}
+
+#[test]
+fn test_deposit_batch() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+    client.configure_rate_limit(&ctx.native_token, &300, &0);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    ctx.mint_native_token(&ctx.depositor, 50000 + client.quote_deposit_fee(&true));
+
+    let to = String::from_str(&ctx.env, "icon01/hxjkdvhui");
+    let deposits = vec![
+        &ctx.env,
+        (ctx.token.clone(), 100000u128),
+        (ctx.native_token.clone(), 50000u128),
+    ];
+    client.deposit_batch(
+        &ctx.depositor,
+        &deposits,
+        &Option::Some(to.clone()),
+        &Option::None,
+    );
+    assert_eq!(token_client.balance(&ctx.registry), 100000);
+    assert_eq!(ctx.get_native_token_balance(&ctx.registry), 50000);
+    assert_eq!(ctx.get_native_token_balance(&ctx.depositor), 0);
+
+    let events = ctx.contract_events();
+    assert_eq!(
+        events.slice(events.len() - 2..),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "deposit"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                DepositEvent {
+                    from: ctx.depositor.clone(),
+                    to: to.clone(),
+                    amount: 100000,
+                    fee: 0,
+                    sn: 1,
+                }
+                .into_val(&ctx.env)
+            ),
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "deposit"),
+                    EVENT_VERSION,
+                    ctx.native_token.clone()
+                )
+                    .into_val(&ctx.env),
+                DepositEvent {
+                    from: ctx.depositor.clone(),
+                    to,
+                    amount: 50000,
+                    fee: 0,
+                    sn: 1,
+                }
+                .into_val(&ctx.env)
+            )
+        ]
+    );
+
+    let data = DepositBatchRevert::new(
+        vec![&ctx.env, ctx.token.clone(), ctx.native_token.clone()],
+        ctx.depositor.clone(),
+        vec![&ctx.env, 100000, 50000],
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "DepositBatchRevert"));
+    let decoded = DepositBatchRevert::decode(&ctx.env, data.clone());
+    assert_eq!(decoded.to, ctx.depositor);
+    assert_eq!(decoded.amounts, vec![&ctx.env, 100000, 50000]);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.xcall_client.get_network_address(), &data, &sources);
+    assert_eq!(token_client.balance(&ctx.depositor), 100000);
+    assert_eq!(ctx.get_native_token_balance(&ctx.depositor), 50000);
+    assert_eq!(token_client.balance(&ctx.registry), 0);
+    assert_eq!(ctx.get_native_token_balance(&ctx.registry), 0);
+}
+
+#[test]
+fn test_deposit_batch_message_encoding() {
+    let ctx = TestContext::default();
+    let message = DepositBatch::new(
+        vec![
+            &ctx.env,
+            ctx.token.to_string(),
+            ctx.native_token.to_string(),
+        ],
+        ctx.depositor.to_string(),
+        String::from_str(&ctx.env, "icon01/hxjkdvhui"),
+        vec![&ctx.env, 0, 127, 100000, u128::MAX],
+        Bytes::from_array(&ctx.env, &[1, 2, 3]),
+    );
+    let decoded = DepositBatch::decode(
+        &ctx.env,
+        message.encode(&ctx.env, String::from_str(&ctx.env, "DepositBatch")),
+    );
+    assert_eq!(decoded.token_addresses, message.token_addresses);
+    assert_eq!(decoded.from, message.from);
+    assert_eq!(decoded.to, message.to);
+    assert_eq!(decoded.amounts, message.amounts);
+    assert_eq!(decoded.data, message.data);
+}
+
+#[test]
+fn test_deposit_batch_rejects_empty_batch() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let result = client.try_deposit_batch(
+        &ctx.depositor,
+        &Vec::new(&ctx.env),
+        &Option::None,
+        &Option::None,
+    );
+    assert_eq!(result, Err(Ok(ContractError::EmptyBatch)));
+}
+
+#[test]
+fn test_deposit_from_with_allowance() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+    client.set_deposit_fee(
+        &ctx.token,
+        &DepositFee {
+            fee_type: FeeType::BasisPoints(100),
+            minimum: 0,
+        },
+    );
+
+    let router = Address::generate(&ctx.env);
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    token_client.approve(&ctx.depositor, &router, &150000, &1000);
+    ctx.mint_native_token(&router, client.quote_deposit_fee(&true));
+
+    let to = String::from_str(&ctx.env, "icon01/hxjkdvhui");
+    client.deposit_from(
+        &router,
+        &ctx.depositor,
+        &ctx.token,
+        &100000,
+        &Option::Some(to.clone()),
+        &Option::None,
+    );
+    let auths = ctx.env.auths();
+    assert_eq!(auths[0].0, router);
+    assert_eq!(
+        auths[0].1.function,
+        AuthorizedFunction::Contract((
+            ctx.registry.clone(),
+            Symbol::new(&ctx.env, "deposit_from"),
+            (
+                router.clone(),
+                ctx.depositor.clone(),
+                ctx.token.clone(),
+                100000u128,
+                Option::Some(to.clone()),
+                Option::<Bytes>::None,
+            )
+                .into_val(&ctx.env)
+        ))
+    );
+    assert_eq!(token_client.balance(&ctx.depositor), 0);
+    assert_eq!(token_client.balance(&ctx.registry), 99000);
+    assert_eq!(token_client.balance(&ctx.fee_collector), 1000);
+    assert_eq!(token_client.allowance(&ctx.depositor, &router), 50000);
+    assert_eq!(ctx.get_native_token_balance(&router), 0);
+    let events = ctx.contract_events();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "deposit"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                DepositEvent {
+                    from: ctx.depositor.clone(),
+                    to,
+                    amount: 99000,
+                    fee: 1000,
+                    sn: 1,
+                }
+                .into_val(&ctx.env)
+            )
+        ]
+    );
+
+    let data = DepositRevert::new(ctx.token.clone(), ctx.depositor.clone(), 99000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "DepositRevert"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.xcall_client.get_network_address(), &data, &sources);
+    assert_eq!(token_client.balance(&ctx.depositor), 99000);
+    assert_eq!(token_client.balance(&router), 0);
+}
+
+#[test]
+fn test_deposit_from_without_allowance() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let router = Address::generate(&ctx.env);
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    token_client.approve(&ctx.depositor, &router, &50000, &1000);
+    ctx.mint_native_token(&router, client.quote_deposit_fee(&true));
+
+    let result = client.try_deposit_from(
+        &router,
+        &ctx.depositor,
+        &ctx.token,
+        &100000,
+        &Option::None,
+        &Option::None,
+    );
+    assert!(result.is_err());
+    assert_eq!(token_client.balance(&ctx.depositor), 100000);
+    assert_eq!(token_client.balance(&ctx.registry), 0);
+}
+
+#[test]
+fn test_claimables_are_paginated() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let recipients = [
+        Address::generate(&ctx.env),
+        Address::generate(&ctx.env),
+        Address::generate(&ctx.env),
+    ];
+    for recipient in recipients.iter() {
+        ctx.deauthorize_token_holder(recipient);
+        let data = WithdrawTo::new(ctx.token.to_string(), recipient.to_string(), 1000)
+            .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+        client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    }
+    assert_eq!(client.get_last_claimable_id(), 3);
+
+    let claimable = |recipient: &Address| Claimable {
+        recipient: recipient.clone(),
+        token: ctx.token.clone(),
+        amount: 1000,
+    };
+    assert_eq!(
+        client.get_claimables(&1, &2),
+        vec![
+            &ctx.env,
+            claimable(&recipients[0]),
+            claimable(&recipients[1])
+        ]
+    );
+    assert_eq!(
+        client.get_claimables(&3, &2),
+        vec![&ctx.env, claimable(&recipients[2])]
+    );
+    assert_eq!(client.get_claimables(&4, &2), Vec::new(&ctx.env));
+
+    // Claimed balances leave a gap, a new claimable takes the next id.
+    token::StellarAssetClient::new(&ctx.env, &ctx.token).set_authorized(&recipients[1], &true);
+    client.claim(&recipients[1], &recipients[1], &ctx.token, &recipients[1]);
+    assert_eq!(
+        client.get_claimables(&1, &3),
+        vec![
+            &ctx.env,
+            claimable(&recipients[0]),
+            claimable(&recipients[2])
+        ]
+    );
+
+    let data = WithdrawTo::new(ctx.token.to_string(), recipients[0].to_string(), 1000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(client.get_last_claimable_id(), 3);
+    assert_eq!(client.get_claimable(&recipients[0], &ctx.token), 2000);
+}
+
+#[test]
+fn test_failed_payout_is_escrowed_and_claimed() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    ctx.deauthorize_token_holder(&ctx.withdrawer);
+
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 40000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "payout_escrowed"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                WithdrawEvent {
+                    to: ctx.withdrawer.clone(),
+                    amount: 40000,
+                }
+                .into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(token_client.balance(&ctx.registry), 100000);
+    assert_eq!(client.get_claimable(&ctx.withdrawer, &ctx.token), 40000);
+
+    // A second failed payout adds to the same claimable.
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(
+        client.get_claimables(&1, &10),
+        vec![
+            &ctx.env,
+            Claimable {
+                recipient: ctx.withdrawer.clone(),
+                token: ctx.token.clone(),
+                amount: 80000,
+            }
+        ]
+    );
+
+    // Reading the claimable keeps it and its index from being archived.
+    client.extend_ttl();
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.sequence_number += DAY_IN_LEDGERS + 1);
+    assert_eq!(client.get_claimable(&ctx.withdrawer, &ctx.token), 80000);
+    ctx.env.as_contract(&client.address, || {
+        let storage = ctx.env.storage().persistent();
+        let keys = [
+            DataKey::Claimable(ctx.withdrawer.clone(), ctx.token.clone()),
+            DataKey::ClaimableId(ctx.withdrawer.clone(), ctx.token.clone()),
+            DataKey::ClaimableEntry(1),
+        ];
+        for key in keys.iter() {
+            assert_eq!(storage.get_ttl(key), INSTANCE_BUMP_AMOUNT);
+        }
+    });
+
+    let new_address = Address::generate(&ctx.env);
+    client.set_guardian(&ctx.admin);
+    client.pause(&Some(ctx.token.clone()), &false, &true);
+    assert_eq!(
+        client.try_claim(&ctx.withdrawer, &ctx.withdrawer, &ctx.token, &new_address),
+        Err(Ok(ContractError::Paused))
+    );
+    client.unpause(&Some(ctx.token.clone()), &false, &true);
+
+    client.claim(&ctx.withdrawer, &ctx.withdrawer, &ctx.token, &new_address);
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            ctx.withdrawer.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "claim"),
+                    (
+                        ctx.withdrawer.clone(),
+                        ctx.withdrawer.clone(),
+                        ctx.token.clone(),
+                        new_address.clone(),
+                    )
+                        .into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "claimable_claimed"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                (ctx.withdrawer.clone(), new_address.clone(), 80000u128).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(token_client.balance(&new_address), 80000);
+    assert_eq!(token_client.balance(&ctx.registry), 20000);
+    assert_eq!(client.get_claimable(&ctx.withdrawer, &ctx.token), 0);
+    assert_eq!(client.get_claimables(&1, &10), Vec::new(&ctx.env));
+    assert_eq!(
+        client.try_claim(&ctx.withdrawer, &ctx.withdrawer, &ctx.token, &new_address),
+        Err(Ok(ContractError::NothingToClaim))
+    );
+}
+
+#[test]
+fn test_claim_by_rescuer() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    ctx.deauthorize_token_holder(&ctx.depositor);
+
+    let data = DepositRevert::new(ctx.token.clone(), ctx.depositor.clone(), 50000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "DepositRevert"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.xcall_client.get_network_address(), &data, &sources);
+    assert_eq!(client.get_claimable(&ctx.depositor, &ctx.token), 50000);
+
+    let rescuer = Address::generate(&ctx.env);
+    let new_address = Address::generate(&ctx.env);
+    assert_eq!(
+        client.try_claim(&rescuer, &ctx.depositor, &ctx.token, &new_address),
+        Err(Ok(ContractError::OnlyRecipientOrRescuer))
+    );
+
+    client.set_rescuer(&rescuer);
+    assert_eq!(client.get_rescuer(), rescuer);
+    client.claim(&rescuer, &ctx.depositor, &ctx.token, &new_address);
+    assert_eq!(token_client.balance(&new_address), 50000);
+    assert_eq!(client.get_claimable(&ctx.depositor, &ctx.token), 0);
+}
+
+#[test]
+fn test_token_decimals_scale_deposits_and_withdrawals() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    assert_eq!(
+        client.try_remove_token_decimals(&ctx.token),
+        Err(Ok(ContractError::TokenDecimalsNotSet))
+    );
+    assert_eq!(
+        client.try_set_token_decimals(&ctx.token, &60),
+        Err(Ok(ContractError::InvalidDecimals))
+    );
+    client.set_token_decimals(&ctx.token, &5);
+    let decimals = TokenDecimals { local: 7, hub: 5 };
+    assert_eq!(
+        client.get_token_decimals(&ctx.token),
+        Some(decimals.clone())
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "token_decimals_updated"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                decimals.into_val(&ctx.env)
+            )
+        ]
+    );
+
+    // Dust below the hub precision stays with the depositor.
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100123);
+    ctx.mint_native_token(&ctx.depositor, client.quote_deposit_fee(&true));
+    client.deposit(
+        &ctx.depositor,
+        &ctx.token,
+        &100123,
+        &Option::None,
+        &Option::None,
+    );
+    assert_eq!(token_client.balance(&ctx.registry), 100100);
+    assert_eq!(token_client.balance(&ctx.depositor), 23);
+
+    // Hub amounts are scaled up to the token decimals.
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 500)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 50000);
+
+    // Hub amounts that can't be paid out exactly are rejected.
+    client.set_token_decimals(&ctx.token, &9);
+    let dust_data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 501)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    assert_eq!(
+        client.try_handle_call_message(&ctx.icon_asset_manager, &dust_data, &sources),
+        Err(Ok(ContractError::AmountHasDust))
+    );
+
+    client.remove_token_decimals(&ctx.token);
+    assert_eq!(client.get_token_decimals(&ctx.token), None);
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 50500);
+}
+
+#[test]
+fn test_deposit_limit() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    assert_eq!(client.get_available_deposit(&ctx.token), None);
+    client.set_deposit_limit(&ctx.token, &100, &1000);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "deposit_limit_updated"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                (100u64, 1000u128).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(
+        client.get_deposit_limit(&ctx.token),
+        Some(DepositLimit {
+            period: 100,
+            max_deposit: 1000,
+            deposited: 0,
+            last_update: ctx.env.ledger().timestamp(),
+        })
+    );
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &10000);
+    ctx.mint_native_token(&ctx.depositor, 3 * client.quote_deposit_fee(&true));
+
+    client.deposit(&ctx.depositor, &ctx.token, &600, &None, &None);
+    assert_eq!(client.get_available_deposit(&ctx.token), Some(400));
+    assert_eq!(
+        client.try_deposit(&ctx.depositor, &ctx.token, &500, &None, &None),
+        Err(Ok(ContractError::ExceedsDepositLimit))
+    );
+
+    // Half of the period releases half of the capacity.
+    ctx.env.ledger().with_mut(|li| li.timestamp += 50);
+    assert_eq!(client.get_available_deposit(&ctx.token), Some(900));
+    client.deposit(&ctx.depositor, &ctx.token, &500, &None, &None);
+    assert_eq!(client.get_available_deposit(&ctx.token), Some(400));
+
+    client.remove_deposit_limit(&ctx.token);
+    assert_eq!(client.get_available_deposit(&ctx.token), None);
+    assert_eq!(
+        client.try_remove_deposit_limit(&ctx.token),
+        Err(Ok(ContractError::DepositLimitNotSet))
+    );
+    client.deposit(&ctx.depositor, &ctx.token, &5000, &None, &None);
+}
+
+#[test]
+fn test_rate_limit_windows() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &1000000);
+
+    // 20% per day and 5% per hour.
+    client.configure_rate_limit(&ctx.token, &86400, &8000);
+    client.add_rate_limit_window(&ctx.token, &3600, &9500);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "rate_limit_window_added"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                (3600u64, 9500u32).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(
+        client.get_rate_limit_windows(&ctx.token),
+        vec![
+            &ctx.env,
+            RateLimitWindow {
+                period: 3600,
+                percentage: 9500,
+                last_update: ctx.env.ledger().timestamp(),
+                current_limit: 0,
+            }
+        ]
+    );
+    assert_eq!(
+        client.try_add_rate_limit_window(&ctx.token, &86400, &9000),
+        Err(Ok(ContractError::RateLimitWindowExists))
+    );
+    assert_eq!(
+        client.try_update_rate_limit(&ctx.token, &3600, &8000),
+        Err(Ok(ContractError::RateLimitWindowExists))
+    );
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 950000);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 50000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 50000);
+
+    // The hourly window is exhausted while the daily one still has capacity.
+    assert_eq!(
+        client.get_window_capacities(&ctx.token),
+        vec![
+            &ctx.env,
+            WindowCapacity {
+                period: 86400,
+                percentage: 8000,
+                reserve: 800000,
+                available: 150000,
+            },
+            WindowCapacity {
+                period: 3600,
+                percentage: 9500,
+                reserve: 950000,
+                available: 0,
+            }
+        ]
+    );
+
+    // Each window decays over its own period.
+    ctx.env.ledger().with_mut(|li| li.timestamp += 1800);
+    assert_eq!(
+        client.get_window_capacities(&ctx.token),
+        vec![
+            &ctx.env,
+            WindowCapacity {
+                period: 86400,
+                percentage: 8000,
+                reserve: 796042,
+                available: 153958,
+            },
+            WindowCapacity {
+                period: 3600,
+                percentage: 9500,
+                reserve: 926250,
+                available: 23750,
+            }
+        ]
+    );
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 926250);
+
+    client.remove_rate_limit_window(&ctx.token, &3600);
+    assert_eq!(client.get_withdraw_limit(&ctx.token), 796042);
+    assert_eq!(
+        client.try_remove_rate_limit_window(&ctx.token, &3600),
+        Err(Ok(ContractError::RateLimitWindowNotFound))
+    );
+}
+
+#[test]
+fn test_simulate_withdraw() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &1000000);
+    client.configure_rate_limit(&ctx.token, &300, &9000);
+
+    let now = ctx.env.ledger().timestamp();
+    assert_eq!(
+        client.simulate_withdraw(&ctx.token, &50000, &None),
+        WithdrawSimulation {
+            allowed: true,
+            reserve: 900000,
+            max_withdrawable: 100000,
+            withdrawable_at: Some(now),
+            paused: false,
+            requires_approval: false,
+            recipient_blocked: false,
+        }
+    );
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 100000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 100000);
+
+    // 50000 of the 90000 above the new minimum reserve takes 167 seconds.
+    let rate_limit = client.get_rate_limit(&ctx.token);
+    assert_eq!(
+        client.simulate_withdraw(&ctx.token, &50000, &None),
+        WithdrawSimulation {
+            allowed: false,
+            reserve: 900000,
+            max_withdrawable: 0,
+            withdrawable_at: Some(now + 167),
+            paused: false,
+            requires_approval: false,
+            recipient_blocked: false,
+        }
+    );
+    assert_eq!(client.get_rate_limit(&ctx.token), rate_limit);
+    assert_eq!(
+        client
+            .simulate_withdraw(&ctx.token, &100000, &None)
+            .withdrawable_at,
+        None
+    );
+
+    ctx.env.ledger().with_mut(|li| li.timestamp += 166);
+    assert!(!client.simulate_withdraw(&ctx.token, &50000, &None).allowed);
+    ctx.env.ledger().with_mut(|li| li.timestamp += 1);
+    assert!(client.simulate_withdraw(&ctx.token, &50000, &None).allowed);
+
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 50000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 150000);
+}
+
+#[test]
+fn test_simulate_withdraw_gates() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+    let recipient = Some(ctx.withdrawer.clone());
+    let open = WithdrawSimulation {
+        allowed: true,
+        reserve: 0,
+        max_withdrawable: 100000,
+        withdrawable_at: Some(ctx.env.ledger().timestamp()),
+        paused: false,
+        requires_approval: false,
+        recipient_blocked: false,
+    };
+    assert_eq!(
+        client.simulate_withdraw(&ctx.token, &20000, &recipient),
+        open
+    );
+
+    // Pause
+    client.set_guardian(&ctx.admin);
+    client.pause(&Some(ctx.token.clone()), &false, &true);
+    assert_eq!(
+        client.simulate_withdraw(&ctx.token, &20000, &recipient),
+        WithdrawSimulation {
+            allowed: false,
+            withdrawable_at: None,
+            paused: true,
+            ..open.clone()
+        }
+    );
+    client.unpause(&Some(ctx.token.clone()), &false, &true);
+    assert_eq!(
+        client.simulate_withdraw(&ctx.token, &20000, &recipient),
+        open
+    );
+
+    // Approval threshold
+    let guardians = vec![&ctx.env, Address::generate(&ctx.env)];
+    client.configure_approvals(&guardians, &1, &1000);
+    client.set_approval_threshold(&ctx.token, &10000);
+    assert_eq!(
+        client.simulate_withdraw(&ctx.token, &20000, &recipient),
+        WithdrawSimulation {
+            allowed: false,
+            withdrawable_at: None,
+            requires_approval: true,
+            ..open.clone()
+        }
+    );
+    assert!(
+        client
+            .simulate_withdraw(&ctx.token, &10000, &recipient)
+            .allowed
+    );
+    client.set_approval_threshold(&ctx.token, &50000);
+
+    // Recipient limit, only checked when a recipient is given.
+    client.set_recipient_limit(
+        &ctx.token,
+        &RecipientLimit {
+            period: 100,
+            max_amount: 10000,
+            cooldown: 0,
+        },
+    );
+    assert_eq!(
+        client.simulate_withdraw(&ctx.token, &20000, &recipient),
+        WithdrawSimulation {
+            allowed: false,
+            withdrawable_at: None,
+            recipient_blocked: true,
+            ..open.clone()
+        }
+    );
+    assert_eq!(client.simulate_withdraw(&ctx.token, &20000, &None), open);
+    assert!(
+        client
+            .simulate_withdraw(&ctx.token, &10000, &recipient)
+            .allowed
+    );
+}
+
+#[test]
+fn test_rate_limit_group() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &600000);
+    ctx.mint_native_token(&ctx.registry, 400000);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+    client.configure_rate_limit(&ctx.native_token, &300, &0);
+
+    let group = Symbol::new(&ctx.env, "usd");
+    client.configure_rate_limit_group(&group, &300, &9000);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "rate_limit_group_configured"),
+                    EVENT_VERSION,
+                    group.clone()
+                )
+                    .into_val(&ctx.env),
+                (300u64, 9000u32).into_val(&ctx.env)
+            )
+        ]
+    );
+    client.add_to_rate_limit_group(&group, &ctx.token);
+    client.add_to_rate_limit_group(&group, &ctx.native_token);
+    assert_eq!(
+        client.try_add_to_rate_limit_group(&group, &ctx.token),
+        Err(Ok(ContractError::TokenAlreadyInRateLimitGroup))
+    );
+    assert_eq!(
+        client.get_token_rate_limit_group(&ctx.native_token),
+        Some(group.clone())
+    );
+    assert_eq!(
+        client.get_rate_limit_group_capacity(&group),
+        WindowCapacity {
+            period: 300,
+            percentage: 9000,
+            reserve: 900000,
+            available: 100000,
+        }
+    );
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 60000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 60000);
+
+    // The withdrawal of the first token used up most of the shared capacity.
+    let now = ctx.env.ledger().timestamp();
+    assert!(
+        client
+            .simulate_withdraw(&ctx.native_token, &40000, &None)
+            .allowed
+    );
+    assert_eq!(
+        client.simulate_withdraw(&ctx.native_token, &60000, &None),
+        WithdrawSimulation {
+            allowed: false,
+            reserve: 360000,
+            max_withdrawable: 40000,
+            withdrawable_at: Some(now + 64),
+            paused: false,
+            requires_approval: false,
+            recipient_blocked: false,
+        }
+    );
+    let data = WithdrawTo::new(
+        ctx.native_token.to_string(),
+        ctx.withdrawer.to_string(),
+        60000,
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    assert_eq!(client.get_pending_withdrawal(&1).amount, 60000);
+
+    client.remove_from_rate_limit_group(&ctx.native_token);
+    assert_eq!(
+        client.get_rate_limit_group(&group),
+        Some(RateLimitGroup {
+            tokens: vec![&ctx.env, ctx.token.clone()],
+            limit: RateLimitWindow {
+                period: 300,
+                percentage: 9000,
+                last_update: now,
+                current_limit: 900000,
+            },
+        })
+    );
+    assert!(
+        client
+            .simulate_withdraw(&ctx.native_token, &60000, &None)
+            .allowed
+    );
+
+    client.remove_rate_limit_group(&group);
+    assert_eq!(client.get_token_rate_limit_group(&ctx.token), None);
+    assert_eq!(
+        client.try_remove_rate_limit_group(&group),
+        Err(Ok(ContractError::RateLimitGroupNotFound))
+    );
+}
+
+#[test]
+fn test_withdrawal_approvals() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    assert_eq!(
+        client.try_set_approval_threshold(&ctx.token, &10000),
+        Err(Ok(ContractError::ApprovalsNotConfigured))
+    );
+    let guardian1 = Address::generate(&ctx.env);
+    let guardian2 = Address::generate(&ctx.env);
+    let guardian3 = Address::generate(&ctx.env);
+    let guardians = vec![
+        &ctx.env,
+        guardian1.clone(),
+        guardian2.clone(),
+        guardian3.clone(),
+    ];
+    assert_eq!(
+        client.try_configure_approvals(&guardians, &4, &1000),
+        Err(Ok(ContractError::InvalidApprovalConfig))
+    );
+    client.configure_approvals(&guardians, &2, &1000);
+    client.set_approval_threshold(&ctx.token, &10000);
+    assert_eq!(client.get_approval_threshold(&ctx.token), Some(10000));
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let withdraw = |amount: u128| {
+        let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), amount)
+            .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+        client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+    };
+    withdraw(5000);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 5000);
+
+    withdraw(20000);
+    let withdrawal = PendingWithdrawal {
+        token: ctx.token.clone(),
+        to: ctx.withdrawer.clone(),
+        amount: 20000,
+        unlock_time: ctx.env.ledger().timestamp() + 1000,
+        refund: false,
+    };
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "withdrawal_awaiting_approval"),
+                    EVENT_VERSION,
+                    1u64
+                )
+                    .into_val(&ctx.env),
+                withdrawal.into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(
+        client.try_claim_withdrawal(&1),
+        Err(Ok(ContractError::WithdrawalLocked))
+    );
+
+    assert_eq!(
+        client.try_approve_withdrawal(&ctx.depositor, &1),
+        Err(Ok(ContractError::OnlyApprovalGuardian))
+    );
+    client.approve_withdrawal(&guardian1, &1);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "withdrawal_approved"),
+                    EVENT_VERSION,
+                    1u64
+                )
+                    .into_val(&ctx.env),
+                (guardian1.clone(), 1u32).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(
+        client.try_approve_withdrawal(&guardian1, &1),
+        Err(Ok(ContractError::AlreadyApproved))
+    );
+    assert_eq!(
+        client.get_withdrawal_approvals(&1),
+        vec![&ctx.env, guardian1.clone()]
+    );
+
+    client.approve_withdrawal(&guardian2, &1);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "withdrawal_released"),
+                    EVENT_VERSION,
+                    1u64
+                )
+                    .into_val(&ctx.env),
+                ().into_val(&ctx.env)
+            )
+        ]
+    );
+    client.claim_withdrawal(&1);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 25000);
+
+    // A single guardian can reject a withdrawal awaiting approval.
+    withdraw(30000);
+    client.reject_withdrawal(&guardian3, &2);
+    assert_eq!(
+        client.try_get_pending_withdrawal(&2),
+        Err(Ok(ContractError::WithdrawalNotFound))
+    );
+
+    // Without enough approvals a withdrawal unlocks after the maximum delay.
+    withdraw(30000);
+    client.approve_withdrawal(&guardian3, &3);
+    ctx.env.ledger().with_mut(|li| li.timestamp += 1000);
+    assert_eq!(
+        client.try_reject_withdrawal(&guardian1, &3),
+        Err(Ok(ContractError::WithdrawalUnlocked))
+    );
+    client.claim_withdrawal(&3);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 55000);
+    assert_eq!(
+        client.try_get_withdrawal_approvals(&3),
+        Err(Ok(ContractError::WithdrawalNotAwaitingApproval))
+    );
+
+    // Approvals of guardians that were removed don't count.
+    withdraw(20000);
+    client.approve_withdrawal(&guardian1, &4);
+    let guardian4 = Address::generate(&ctx.env);
+    client.configure_approvals(
+        &vec![
+            &ctx.env,
+            guardian2.clone(),
+            guardian3.clone(),
+            guardian4.clone(),
+        ],
+        &2,
+        &1000,
+    );
+    client.approve_withdrawal(&guardian2, &4);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "withdrawal_approved"),
+                    EVENT_VERSION,
+                    4u64
+                )
+                    .into_val(&ctx.env),
+                (guardian2.clone(), 1u32).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(
+        client.try_claim_withdrawal(&4),
+        Err(Ok(ContractError::WithdrawalLocked))
+    );
+    client.approve_withdrawal(&guardian4, &4);
+    client.claim_withdrawal(&4);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 75000);
+
+    assert_eq!(
+        client.try_configure_approvals(&guardians, &2, &0),
+        Err(Ok(ContractError::InvalidApprovalConfig))
+    );
+}
+
+#[test]
+fn test_pending_withdrawal_ttl_is_extended() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+    let guardian1 = Address::generate(&ctx.env);
+    let guardian2 = Address::generate(&ctx.env);
+    client.configure_approvals(&vec![&ctx.env, guardian1.clone(), guardian2], &2, &1000);
+    client.set_approval_threshold(&ctx.token, &10000);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 20000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    let ttls = || {
+        ctx.env.as_contract(&client.address, || {
+            (
+                ctx.env
+                    .storage()
+                    .persistent()
+                    .get_ttl(&DataKey::PendingWithdrawal(1)),
+                ctx.env
+                    .storage()
+                    .persistent()
+                    .get_ttl(&DataKey::WithdrawalApprovals(1)),
+            )
+        })
+    };
+    assert_eq!(ttls(), (INSTANCE_BUMP_AMOUNT, INSTANCE_BUMP_AMOUNT));
+
+    // `extend_ttl` keeps the instance alive but leaves queued withdrawals alone.
+    client.extend_ttl();
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.sequence_number += DAY_IN_LEDGERS + 1);
+    let expiring = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS - 1;
+    assert_eq!(ttls(), (expiring, expiring));
+
+    client.approve_withdrawal(&guardian1, &1);
+    assert_eq!(ttls(), (INSTANCE_BUMP_AMOUNT, INSTANCE_BUMP_AMOUNT));
+}
+
+#[test]
+fn test_recipient_limits() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let token_client = token::Client::new(&ctx.env, &ctx.token);
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let limit = RecipientLimit {
+        period: 100,
+        max_amount: 10000,
+        cooldown: 10,
+    };
+    client.set_recipient_limit(&ctx.token, &limit);
+    assert_eq!(client.get_recipient_limit(&ctx.token), Some(limit));
+    assert_eq!(
+        client.get_recipient_available(&ctx.withdrawer, &ctx.token),
+        Some(10000)
+    );
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let withdraw_to = |to: &Address, amount: u128| {
+        WithdrawTo::new(ctx.token.to_string(), to.to_string(), amount)
+            .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"))
+    };
+    client.handle_call_message(
+        &ctx.icon_asset_manager,
+        &withdraw_to(&ctx.withdrawer, 6000),
+        &sources,
+    );
+    assert_eq!(token_client.balance(&ctx.withdrawer), 6000);
+
+    // A second withdrawal within the cooldown is queued.
+    client.handle_call_message(
+        &ctx.icon_asset_manager,
+        &withdraw_to(&ctx.withdrawer, 1000),
+        &sources,
+    );
+    assert_eq!(token_client.balance(&ctx.withdrawer), 6000);
+    let queued = client.get_pending_withdrawal(&1);
+    assert_eq!(queued.amount, 1000);
+    assert_eq!(
+        queued.unlock_time,
+        ctx.env.ledger().timestamp() + client.get_withdrawal_delay()
+    );
+
+    // Refunds of reverted deposits are not limited.
+    let data = DepositRevert::new(ctx.token.clone(), ctx.withdrawer.clone(), 500)
+        .encode(&ctx.env, String::from_str(&ctx.env, "DepositRevert"));
+    client.handle_call_message(&ctx.xcall_client.get_network_address(), &data, &sources);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 6500);
+
+    // Neither the queued withdrawal nor the refund count against the limit.
+    ctx.env.ledger().with_mut(|li| li.timestamp += 10);
+    assert_eq!(
+        client.get_recipient_available(&ctx.withdrawer, &ctx.token),
+        Some(5000)
+    );
+    client.handle_call_message(
+        &ctx.icon_asset_manager,
+        &withdraw_to(&ctx.withdrawer, 6000),
+        &sources,
+    );
+    assert_eq!(client.get_pending_withdrawal(&2).amount, 6000);
+    client.handle_call_message(
+        &ctx.icon_asset_manager,
+        &withdraw_to(&ctx.withdrawer, 5000),
+        &sources,
+    );
+    assert_eq!(token_client.balance(&ctx.withdrawer), 11500);
+
+    // Other recipients have their own limits.
+    client.handle_call_message(
+        &ctx.icon_asset_manager,
+        &withdraw_to(&ctx.depositor, 10000),
+        &sources,
+    );
+    assert_eq!(token_client.balance(&ctx.depositor), 10000);
+
+    client.set_recipient_exemption(&ctx.withdrawer, &true);
+    assert_eq!(
+        client.get_exempt_recipients(),
+        vec![&ctx.env, ctx.withdrawer.clone()]
+    );
+    assert_eq!(
+        client.get_recipient_available(&ctx.withdrawer, &ctx.token),
+        None
+    );
+    client.handle_call_message(
+        &ctx.icon_asset_manager,
+        &withdraw_to(&ctx.withdrawer, 20000),
+        &sources,
+    );
+    assert_eq!(token_client.balance(&ctx.withdrawer), 31500);
+
+    client.set_recipient_exemption(&ctx.withdrawer, &false);
+    assert_eq!(client.get_exempt_recipients(), Vec::new(&ctx.env));
+
+    // Queued withdrawals can be claimed after the withdrawal delay, as long as
+    // they fit within the recipient limit by then.
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += client.get_withdrawal_delay());
+    client.claim_withdrawal(&1);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 32500);
+    assert_eq!(
+        client.try_claim_withdrawal(&2),
+        Err(Ok(ContractError::ExceedsWithdrawLimit))
+    );
+    ctx.env.ledger().with_mut(|li| li.timestamp += 10);
+    client.claim_withdrawal(&2);
+    assert_eq!(token_client.balance(&ctx.withdrawer), 38500);
+    assert_eq!(
+        client.get_recipient_available(&ctx.withdrawer, &ctx.token),
+        Some(4000)
+    );
+    client.set_recipient_limit(
+        &ctx.token,
+        &RecipientLimit {
+            period: 0,
+            max_amount: 0,
+            cooldown: 0,
+        },
+    );
+    assert_eq!(client.get_recipient_limit(&ctx.token), None);
+}
+
+#[test]
+fn test_withdraw_to_muxed_address() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let muxed = String::from_str(
+        &ctx.env,
+        "MAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSAAAAAEPXD6YEZO4WE",
+    );
+    let account = Address::from_string(&String::from_str(
+        &ctx.env,
+        "GAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSABOV",
+    ));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), muxed, 1000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    let events = ctx.contract_events();
+    assert!(events.contains(&(
+        ctx.registry.clone(),
+        (
+            Symbol::new(&ctx.env, "muxed_withdraw_to"),
+            EVENT_VERSION,
+            ctx.token.clone()
+        )
+            .into_val(&ctx.env),
+        (account.clone(), 1234567890123u64, 1000u128).into_val(&ctx.env)
+    )));
+    // The test ledger has no trustline for the account, so the payout is escrowed.
+    assert_eq!(client.get_claimable(&account, &ctx.token), 1000);
+
+    let data = WithdrawTo::new(
+        ctx.token.to_string(),
+        String::from_str(
+            &ctx.env,
+            "MAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSAAAAAEPXD6YEZO4WA",
+        ),
+        1000,
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    assert_eq!(
+        client.try_handle_call_message(&ctx.icon_asset_manager, &data, &sources),
+        Err(Ok(ContractError::InvalidAddress))
+    );
+}
+
+#[test]
+fn test_withdraw_to_address_with_bad_checksum() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.registry, &100000);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(
+        ctx.token.to_string(),
+        String::from_str(
+            &ctx.env,
+            "GAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSABOA",
+        ),
+        1000,
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    assert_eq!(
+        client.try_handle_call_message(&ctx.icon_asset_manager, &data, &sources),
+        Err(Ok(ContractError::InvalidAddress))
+    );
+
+    // A contract address can't be encoded with the account version byte.
+    let data = WithdrawTo::new(
+        ctx.token.to_string(),
+        String::from_str(
+            &ctx.env,
+            "CAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSABOV",
+        ),
+        1000,
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    assert_eq!(
+        client.try_handle_call_message(&ctx.icon_asset_manager, &data, &sources),
+        Err(Ok(ContractError::InvalidAddress))
+    );
+    assert_eq!(client.balance_of(&ctx.token), 100000);
+}
+
+#[test]
+fn test_deposit_native() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.native_token, &300, &0);
+
+    let fee = client.quote_deposit_fee(&true);
+    ctx.mint_native_token(&ctx.depositor, 1000 + fee - 1);
+    let to = Option::Some(String::from_str(&ctx.env, "icon01/hxjkdvhui"));
+    assert_eq!(
+        client.try_deposit_native(&ctx.depositor, &1000, &to, &Option::None),
+        Err(Ok(ContractError::InsufficientNativeBalance))
+    );
+    assert_eq!(
+        client.try_deposit(&ctx.depositor, &ctx.native_token, &1000, &to, &Option::None),
+        Err(Ok(ContractError::InsufficientNativeBalance))
+    );
+    assert_eq!(
+        client.try_deposit_batch(
+            &ctx.depositor,
+            &vec![&ctx.env, (ctx.native_token.clone(), 1000u128)],
+            &to,
+            &Option::None
+        ),
+        Err(Ok(ContractError::InsufficientNativeBalance))
+    );
+
+    ctx.mint_native_token(&ctx.depositor, 1);
+    client.deposit_native(&ctx.depositor, &1000, &to, &Option::None);
+    assert_eq!(ctx.get_native_token_balance(&ctx.registry), 1000);
+    assert_eq!(ctx.get_native_token_balance(&ctx.depositor), 0);
+}
+
+#[test]
+fn test_report_reserves() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    ctx.mint_native_token(&ctx.depositor, 3 * client.quote_deposit_fee(&true));
+    client.deposit(
+        &ctx.depositor,
+        &ctx.token,
+        &100000,
+        &Option::Some(String::from_str(&ctx.env, "icon01/hxjkdvhui")),
+        &Option::None,
+    );
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 30000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    // Tokens sent straight to the contract show up in the balance only.
+    stellar_asset_client.mint(&ctx.registry, &500);
+    let flow = ReserveFlow {
+        deposited: 100000,
+        withdrawn: 30000,
+    };
+    assert_eq!(client.get_reserve_flow(&ctx.token), flow);
+
+    assert_eq!(
+        client.try_report_reserves(&ctx.depositor, &Vec::new(&ctx.env)),
+        Err(Ok(ContractError::EmptyBatch))
+    );
+    assert_eq!(
+        client.try_report_reserves(&ctx.depositor, &vec![&ctx.env, ctx.native_token.clone()]),
+        Err(Ok(ContractError::TokenDoesNotExists))
+    );
+
+    client.report_reserves(
+        &ctx.depositor,
+        &vec![&ctx.env, ctx.token.clone(), ctx.token.clone()],
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "reserve_reported"),
+                    EVENT_VERSION,
+                    ctx.token.clone()
+                )
+                    .into_val(&ctx.env),
+                (2u128, flow).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.balance_of(&ctx.token), 70500);
+    assert_eq!(
+        client.get_reserve_flow(&ctx.token),
+        ReserveFlow {
+            deposited: 0,
+            withdrawn: 0
+        }
+    );
+
+    let report = ReserveReport::new(
+        vec![&ctx.env, ctx.token.to_string()],
+        vec![&ctx.env, 70500],
+        vec![&ctx.env, 100000],
+        vec![&ctx.env, 30000],
+    );
+    let decoded = ReserveReport::decode(
+        &ctx.env,
+        report.encode(&ctx.env, String::from_str(&ctx.env, "ReserveReport")),
+    );
+    assert_eq!(decoded.balances, report.balances);
+    assert_eq!(decoded.withdrawn, report.withdrawn);
+}
+
+#[test]
+fn test_token_stats_and_reconcile() {
+    let ctx = TestContext::default();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.token, &300, &0);
+    client.set_guardian(&Address::generate(&ctx.env));
+
+    let stellar_asset_client: token::StellarAssetClient =
+        token::StellarAssetClient::new(&ctx.env, &ctx.token);
+    stellar_asset_client.mint(&ctx.depositor, &100000);
+    ctx.mint_native_token(&ctx.depositor, client.quote_deposit_fee(&true));
+    ctx.env.ledger().with_mut(|li| li.timestamp = 1000);
+    client.deposit(
+        &ctx.depositor,
+        &ctx.token,
+        &100000,
+        &Option::Some(String::from_str(&ctx.env, "icon01/hxjkdvhui")),
+        &Option::None,
+    );
+
+    ctx.env.ledger().with_mut(|li| li.timestamp = 2000);
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 30000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    // Queued withdrawals are not counted until they are paid out.
+    ctx.env.ledger().with_mut(|li| li.timestamp = 3000);
+    client.pause(&Some(ctx.token.clone()), &false, &true);
+    let data = DepositRevert::new(ctx.token.clone(), ctx.depositor.clone(), 10000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "DepositRevert"));
+    client.handle_call_message(&ctx.xcall_client.get_network_address(), &data, &sources);
+    let data = WithdrawTo::new(ctx.token.to_string(), ctx.withdrawer.to_string(), 5000)
+        .encode(&ctx.env, String::from_str(&ctx.env, "WithdrawTo"));
+    client.handle_call_message(&ctx.icon_asset_manager, &data, &sources);
+
+    let mut stats = TokenStats {
+        deposited: 100000,
+        withdrawn: 30000,
+        refunded: 0,
+        pending: 0,
+        deposit_count: 1,
+        withdrawal_count: 1,
+        refund_count: 0,
+        last_deposit: 1000,
+        last_withdrawal: 2000,
+        last_refund: 0,
+    };
+    assert_eq!(client.get_token_stats(&ctx.token), stats);
+    let reconciled = Reconciliation {
+        balance: 70000,
+        expected: 70000,
+        surplus: 0,
+        shortfall: 0,
+    };
+    assert_eq!(client.reconcile(&ctx.token), reconciled);
+
+    // A cancelled withdrawal never leaves the contract.
+    client.cancel_withdrawal(&ctx.admin, &2);
+    assert_eq!(client.get_token_stats(&ctx.token), stats);
+    assert_eq!(client.reconcile(&ctx.token), reconciled);
+
+    ctx.env.ledger().with_mut(|li| li.timestamp = 4000);
+    client.unpause(&Some(ctx.token.clone()), &false, &true);
+    client.claim_withdrawal(&1);
+    stats.refunded = 10000;
+    stats.refund_count = 1;
+    stats.last_refund = 4000;
+    assert_eq!(client.get_token_stats(&ctx.token), stats);
+
+    // Tokens sent straight to the contract are reported as surplus.
+    stellar_asset_client.mint(&ctx.registry, &500);
+    assert_eq!(
+        client.reconcile(&ctx.token),
+        Reconciliation {
+            balance: 60500,
+            expected: 60000,
+            surplus: 500,
+            shortfall: 0,
+        }
+    );
+}
+
+#[test]
+fn test_deposit_native_keeps_minimum_reserve() {
+    let ctx = TestContext::with_native_asset();
+    let client = AssetManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+    client.configure_rate_limit(&ctx.native_token, &300, &0);
+
+    // An account without subentries has to keep two base reserves.
+    ctx.env.ledger().set_base_reserve(5_000_000);
+    let reserve = 10_000_000;
+    let fee = client.quote_deposit_fee(&true) as i64;
+    let to = Option::Some(String::from_str(&ctx.env, "icon01/hxjkdvhui"));
+
+    // The balance covers the deposit and the fee, but only by using the reserve.
+    let account = ctx.create_account([1; 32], reserve + 1000 + fee - 1);
+    assert_eq!(
+        client.try_deposit_native(&account, &1000, &to, &Option::None),
+        Err(Ok(ContractError::InsufficientNativeBalance))
+    );
+
+    let account = ctx.create_account([2; 32], reserve + 1000 + fee);
+    client.deposit_native(&account, &1000, &to, &Option::None);
+
+    // The deposit is collected with a single transfer before the xCall message.
+    let deposit = &ctx.env.auths()[0].1;
+    let transfer = &deposit.sub_invocations[0];
+    assert_eq!(deposit.sub_invocations.len(), 2);
+    assert_eq!(
+        transfer.function,
+        AuthorizedFunction::Contract((
+            ctx.native_token.clone(),
+            Symbol::new(&ctx.env, "transfer"),
+            (account.clone(), ctx.registry.clone(), 1000i128).into_val(&ctx.env)
+        ))
+    );
+    assert_eq!(client.balance_of(&ctx.native_token), 1000);
+    assert_eq!(ctx.get_native_token_balance(&account), reserve as u128);
+
+    // Only the deposit and the xCall fee leave the account.
+    let transfers = ctx
+        .env
+        .events()
+        .all()
+        .iter()
+        .filter(|event| {
+            let from = event
+                .1
+                .get(1)
+                .map(|from| Address::try_from_val(&ctx.env, &from));
+            event.0 == ctx.native_token && from == Some(Ok(account.clone()))
+        })
+        .count();
+    assert_eq!(transfers, 2);
 }
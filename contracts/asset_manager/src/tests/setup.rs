@@ -5,8 +5,15 @@ use crate::contract::{AssetManager, AssetManagerClient};
 
 use crate::config::ConfigData;
 
-use soroban_sdk::Vec;
-use soroban_sdk::{testutils::Address as _, token, Address, Env, String};
+use soroban_rlp::balanced::address_utils::account_address;
+use soroban_sdk::{
+    testutils::{Address as _, Events, IssuerFlags, StellarAssetIssuer},
+    token,
+    xdr::{self, WriteXdr},
+    Address, Bytes, BytesN, Env, String,
+};
+use soroban_sdk::{Val, Vec};
+use std::rc::Rc;
 
 mod xcall {
     soroban_sdk::contractimport!(file = "../../wasm/xcall.wasm");
@@ -22,6 +29,16 @@ mod xcall_manager {
     );
 }
 
+pub mod asset_manager_v0 {
+    soroban_sdk::contractimport!(file = "../../wasm/asset_manager_v0.wasm");
+}
+
+mod asset_manager {
+    soroban_sdk::contractimport!(
+        file = "../../target/wasm32-unknown-unknown/release/asset_manager.wasm"
+    );
+}
+
 use xcall_manager::ConfigData as XcallManagerConfigData;
 
 pub struct TestContext {
@@ -31,11 +48,13 @@ pub struct TestContext {
     pub depositor: Address,
     pub withdrawer: Address,
     pub upgrade_authority: Address,
+    pub fee_collector: Address,
     pub xcall: Address,
     pub xcall_manager: Address,
     pub icon_asset_manager: String,
     pub icon_governance: String,
     pub token: Address,
+    pub token_issuer: StellarAssetIssuer,
     pub centralized_connection: Address,
     pub nid: String,
     pub native_token: Address,
@@ -45,6 +64,7 @@ pub struct TestContext {
 impl TestContext {
     pub fn default() -> Self {
         let env = Env::default();
+        env.budget().reset_unlimited();
         let token_admin = Address::generate(&env);
         let token = env.register_stellar_asset_contract_v2(token_admin.clone());
         let asset_manager = env.register_contract(None, AssetManager);
@@ -58,11 +78,13 @@ impl TestContext {
             depositor: Address::generate(&env),
             withdrawer: Address::generate(&env),
             upgrade_authority: Address::generate(&env),
+            fee_collector: Address::generate(&env),
             xcall: xcall.clone(),
             xcall_manager: xcall_manager,
             icon_asset_manager: String::from_str(&env, "icon01/hxjnfh4u"),
             icon_governance: String::from_str(&env, "icon01/kjdnoi"),
             token: token.address(),
+            token_issuer: token.issuer(),
             centralized_connection: centralized_connection,
             nid: String::from_str(&env, "stellar"),
             native_token: env
@@ -73,6 +95,49 @@ impl TestContext {
         }
     }
 
+    /// Same as `default`, but with the native asset contract as native token, so
+    /// accounts holding it are subject to the minimum reserve.
+    pub fn with_native_asset() -> Self {
+        let mut ctx = Self::default();
+        let asset = xdr::Asset::Native.to_xdr(xdr::Limits::none()).unwrap();
+        ctx.native_token = ctx
+            .env
+            .deployer()
+            .with_stellar_asset(Bytes::from_slice(&ctx.env, &asset))
+            .deploy();
+        ctx
+    }
+
+    /// Creates a `G...` account holding `balance` of the native asset.
+    pub fn create_account(&self, ed25519: [u8; 32], balance: i64) -> Address {
+        let account_id =
+            xdr::AccountId(xdr::PublicKey::PublicKeyTypeEd25519(xdr::Uint256(ed25519)));
+        let key = xdr::LedgerKey::Account(xdr::LedgerKeyAccount {
+            account_id: account_id.clone(),
+        });
+        let entry = xdr::LedgerEntry {
+            data: xdr::LedgerEntryData::Account(xdr::AccountEntry {
+                account_id,
+                balance,
+                flags: 0,
+                home_domain: Default::default(),
+                inflation_dest: None,
+                num_sub_entries: 0,
+                seq_num: xdr::SequenceNumber(0),
+                thresholds: xdr::Thresholds([1; 4]),
+                signers: xdr::VecM::default(),
+                ext: xdr::AccountEntryExt::V0,
+            }),
+            last_modified_ledger_seq: 0,
+            ext: xdr::LedgerEntryExt::V0,
+        };
+        self.env
+            .host()
+            .add_ledger_entry(&Rc::new(key), &Rc::new(entry), None)
+            .unwrap();
+        Address::from_string(&account_address(&self.env, &ed25519))
+    }
+
     pub fn init_context(&self, client: &AssetManagerClient<'static>) {
         self.env.mock_all_auths();
         self.init_xcall_manager_context();
@@ -83,10 +148,35 @@ impl TestContext {
             native_address: self.native_token.clone(),
             icon_asset_manager: self.icon_asset_manager.clone(),
             upgrade_authority: self.upgrade_authority.clone(),
+            fee_collector: self.fee_collector.clone(),
         };
         client.initialize(&self.registry, &self.admin, &config);
     }
 
+    /// Deploys and initializes the AssetManager as it was released before
+    /// schema versioning, to test upgrades and migrations against it.
+    pub fn deploy_previous_version(&self) -> Address {
+        self.env.mock_all_auths();
+        self.init_xcall_manager_context();
+        self.init_xcall_state();
+        let address = self
+            .env
+            .register_contract_wasm(None, asset_manager_v0::WASM);
+        let config = asset_manager_v0::ConfigData {
+            xcall: self.xcall.clone(),
+            xcall_manager: self.xcall_manager.clone(),
+            native_address: self.native_token.clone(),
+            icon_asset_manager: self.icon_asset_manager.clone(),
+            upgrade_authority: self.upgrade_authority.clone(),
+        };
+        asset_manager_v0::Client::new(&self.env, &address).initialize(
+            &address,
+            &self.admin,
+            &config,
+        );
+        address
+    }
+
     pub fn init_xcall_manager_context(&self) {
         let client = self::xcall_manager::Client::new(&self.env, &self.xcall_manager);
         let config = XcallManagerConfigData {
@@ -134,15 +224,53 @@ impl TestContext {
         connection_client.set_fee(&self.nid, &message_fee, &response_fee);
     }
 
+    pub fn set_connection_fee(&self, nid: &String, message_fee: u128, response_fee: u128) {
+        let connection_client = connection::Client::new(&self.env, &self.centralized_connection);
+        connection_client.set_fee(nid, &message_fee, &response_fee);
+    }
+
     pub fn mint_native_token(&self, address: &Address, amount: u128) {
         let native_token_client = token::StellarAssetClient::new(&self.env, &self.native_token);
         native_token_client.mint(&address, &(*&amount as i128));
     }
 
+    /// Revokes the authorization of `address` to hold `token`, so transfers to it fail.
+    pub fn deauthorize_token_holder(&self, address: &Address) {
+        self.token_issuer.set_flag(IssuerFlags::RevocableFlag);
+        token::StellarAssetClient::new(&self.env, &self.token).set_authorized(address, &false);
+    }
+
     pub fn get_native_token_balance(&self, address: &Address) -> u128 {
         let native_token_client = token::TokenClient::new(&self.env, &self.native_token);
         let balance = native_token_client.balance(address);
 
         *&balance as u128
     }
+
+    pub fn upload_asset_manager_wasm(&self) -> BytesN<32> {
+        self.env
+            .deployer()
+            .upload_contract_wasm(asset_manager::WASM)
+    }
+
+    pub fn upload_xcall_manager_wasm(&self) -> BytesN<32> {
+        self.env
+            .deployer()
+            .upload_contract_wasm(xcall_manager::WASM)
+    }
+
+    pub fn contract_events(&self) -> Vec<(Address, Vec<Val>, Val)> {
+        let mut events = Vec::new(&self.env);
+        for event in self.env.events().all().iter() {
+            if event.0 == self.registry {
+                events.push_back(event);
+            }
+        }
+        events
+    }
+
+    pub fn last_event(&self) -> Vec<(Address, Vec<Val>, Val)> {
+        let events = self.env.events().all();
+        events.slice(events.len() - 1..)
+    }
 }
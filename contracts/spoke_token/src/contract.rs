@@ -2,15 +2,16 @@
 //! interface.
 use crate::allowance::{read_allowance, spend_allowance, write_allowance};
 use crate::balance::{read_balance, receive_balance, spend_balance};
-use crate::{spoke_token, storage_types};
+use crate::{events, migration, spoke_token, storage_types};
 use crate::spoke_token::xcall_client;
 use crate::errors::ContractError;
 use crate::metadata::{read_decimal, read_name, read_symbol, write_metadata};
-use crate::storage_types::{get_upgrade_authority, set_icon_hub_token, set_upgrade_authority, set_xcall, set_xcall_manager, set_xcall_network_address, get_xcall, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD
+use crate::storage_types::{get_upgrade_authority, remove_pending_upgrade_authority, remove_scheduled_upgrade, set_icon_hub_token, set_pending_upgrade_authority, set_scheduled_upgrade, set_upgrade_authority, set_xcall, set_xcall_manager, set_xcall_network_address, get_xcall, PendingAddress, ScheduledUpgrade, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, PROPOSAL_EXPIRY
 };
 use soroban_sdk::{
     contract, contractimpl, panic_with_error, Address, Bytes, BytesN, Env, String, Vec,
 };
+use soroban_rlp::balanced::decimal_utils::scale_amount;
 use soroban_token_sdk::metadata::TokenMetadata;
 use soroban_token_sdk::TokenUtils;
 pub fn check_nonnegative_amount(amount: i128) {
@@ -42,6 +43,7 @@ impl BalancedDollar {
         set_xcall_manager(&e, xcall_manager);
         set_xcall_network_address(&e, xcall_client(&e, &xcall).get_network_address());
         set_upgrade_authority(&e, upgrade_auth);
+        storage_types::set_schema_version(&e, migration::SCHEMA_VERSION);
     }
 
     pub fn cross_transfer(
@@ -56,6 +58,11 @@ impl BalancedDollar {
         return spoke_token::_cross_transfer(e.clone(), from, amount, to, transfer_data);
     }
 
+    /// Returns the xCall fee, in the native token, that `cross_transfer` currently costs.
+    pub fn quote_cross_transfer_fee(e: Env) -> Result<u128, ContractError> {
+        spoke_token::_quote_cross_transfer_fee(e)
+    }
+
     pub fn handle_call_message(
         e: Env,
         from: String,
@@ -74,16 +81,142 @@ impl BalancedDollar {
         set_xcall_network_address(&e, xcall_client(&e, &xcall).get_network_address());
     }
 
-    pub fn set_upgrade_authority(e: Env, new_upgrade_authority: Address) {
+    pub fn propose_upgrade_authority(e: Env, new_upgrade_authority: Address) {
         let upgrade_authority = get_upgrade_authority(&e).unwrap();
         upgrade_authority.require_auth();
-        set_upgrade_authority(&e, new_upgrade_authority);
+        let proposal = PendingAddress {
+            address: new_upgrade_authority,
+            expires_at: e.ledger().timestamp() + PROPOSAL_EXPIRY,
+        };
+        set_pending_upgrade_authority(&e, proposal.clone());
+        events::upgrade_authority_proposed(&e, proposal);
+    }
+
+    pub fn accept_upgrade_authority(e: Env) -> Result<(), ContractError> {
+        let proposal = storage_types::get_pending_upgrade_authority(&e)
+            .ok_or(ContractError::NoPendingProposal)?;
+        if e.ledger().timestamp() > proposal.expires_at {
+            return Err(ContractError::ProposalExpired);
+        }
+        proposal.address.require_auth();
+
+        remove_pending_upgrade_authority(&e);
+        set_upgrade_authority(&e, proposal.address.clone());
+        events::upgrade_authority_changed(&e, proposal.address);
+        Ok(())
     }
 
-    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+    pub fn cancel_upgrade_auth_proposal(e: Env) -> Result<(), ContractError> {
         let upgrade_authority = get_upgrade_authority(&e).unwrap();
         upgrade_authority.require_auth();
-        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        let proposal = storage_types::get_pending_upgrade_authority(&e)
+            .ok_or(ContractError::NoPendingProposal)?;
+        remove_pending_upgrade_authority(&e);
+        events::upgrade_authority_cancelled(&e, proposal.address);
+        Ok(())
+    }
+
+    pub fn get_pending_upgrade_authority(e: Env) -> Option<PendingAddress> {
+        storage_types::get_pending_upgrade_authority(&e)
+    }
+
+    /// Sets how long a scheduled upgrade has to wait before it can be applied,
+    /// at least `MIN_UPGRADE_DELAY`.
+    pub fn set_upgrade_delay(e: Env, delay: u64) -> Result<(), ContractError> {
+        let admin = spoke_token::xcall_manager_admin(&e);
+        admin.require_auth();
+        if delay < storage_types::MIN_UPGRADE_DELAY {
+            return Err(ContractError::UpgradeDelayTooShort);
+        }
+        storage_types::set_upgrade_delay(&e, delay);
+        events::upgrade_delay_updated(&e, delay);
+        Ok(())
+    }
+
+    /// Sets the decimals bnUSD is accounted in on the ICON hub. Amounts in xCall
+    /// messages are scaled between these and the token decimals.
+    pub fn set_hub_decimals(e: Env, hub_decimals: u32) -> Result<(), ContractError> {
+        let admin = spoke_token::xcall_manager_admin(&e);
+        admin.require_auth();
+        let decimal = read_decimal(&e);
+        if scale_amount(1, decimal, hub_decimals).is_none()
+            || scale_amount(1, hub_decimals, decimal).is_none()
+        {
+            return Err(ContractError::InvalidDecimals);
+        }
+        storage_types::set_hub_decimals(&e, hub_decimals);
+        events::hub_decimals_updated(&e, hub_decimals);
+        Ok(())
+    }
+
+    pub fn get_hub_decimals(e: Env) -> Option<u32> {
+        storage_types::get_hub_decimals(&e)
+    }
+
+    pub fn get_upgrade_delay(e: Env) -> u64 {
+        storage_types::get_upgrade_delay(&e)
+    }
+
+    pub fn get_scheduled_upgrade(e: Env) -> Option<ScheduledUpgrade> {
+        storage_types::get_scheduled_upgrade(&e)
+    }
+
+    pub fn schedule_upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        let upgrade_authority = get_upgrade_authority(&e).unwrap();
+        upgrade_authority.require_auth();
+        if storage_types::get_scheduled_upgrade(&e).is_some() {
+            return Err(ContractError::UpgradeAlreadyScheduled);
+        }
+        let upgrade = ScheduledUpgrade {
+            wasm_hash: new_wasm_hash,
+            executable_at: e.ledger().timestamp() + storage_types::get_upgrade_delay(&e),
+        };
+        set_scheduled_upgrade(&e, upgrade.clone());
+        events::upgrade_scheduled(&e, upgrade);
+        Ok(())
+    }
+
+    pub fn execute_upgrade(e: Env) -> Result<(), ContractError> {
+        let upgrade_authority = get_upgrade_authority(&e).unwrap();
+        upgrade_authority.require_auth();
+        let upgrade = storage_types::get_scheduled_upgrade(&e)
+            .ok_or(ContractError::UpgradeNotScheduled)?;
+        if e.ledger().timestamp() < upgrade.executable_at {
+            return Err(ContractError::UpgradeLocked);
+        }
+        remove_scheduled_upgrade(&e);
+        e.deployer().update_current_contract_wasm(upgrade.wasm_hash.clone());
+        events::upgraded(&e, upgrade.wasm_hash);
+        Ok(())
+    }
+
+    /// The BalancedDollar has no admin of its own, the xcall manager admin
+    /// is allowed to cancel a scheduled upgrade.
+    pub fn cancel_upgrade(e: Env) -> Result<(), ContractError> {
+        let admin = spoke_token::xcall_manager_admin(&e);
+        admin.require_auth();
+        let upgrade = storage_types::get_scheduled_upgrade(&e)
+            .ok_or(ContractError::UpgradeNotScheduled)?;
+        remove_scheduled_upgrade(&e);
+        events::upgrade_cancelled(&e, upgrade.wasm_hash, admin);
+        Ok(())
+    }
+
+    pub fn version(e: Env) -> u32 {
+        storage_types::get_schema_version(&e)
+    }
+
+    pub fn migrate(e: Env) -> Result<(), ContractError> {
+        let upgrade_authority = get_upgrade_authority(&e).unwrap();
+        upgrade_authority.require_auth();
+        let from = storage_types::get_schema_version(&e);
+        if from >= migration::SCHEMA_VERSION {
+            return Err(ContractError::AlreadyMigrated);
+        }
+        migration::migrate(&e, from);
+        storage_types::set_schema_version(&e, migration::SCHEMA_VERSION);
+        events::migrated(&e, from, migration::SCHEMA_VERSION);
+        Ok(())
     }
 
     pub fn extend_ttl(e: Env) {
@@ -15,5 +15,14 @@ pub enum ContractError {
     InvalidNetworkAddressLength = 9,
     InvalidNetworkAddress = 10,
     InvalidAmount = 11,
-    Uninitialized = 12
+    Uninitialized = 12,
+    NoPendingProposal = 13,
+    ProposalExpired = 14,
+    UpgradeAlreadyScheduled = 15,
+    UpgradeNotScheduled = 16,
+    UpgradeLocked = 17,
+    AlreadyMigrated = 18,
+    InvalidDecimals = 19,
+    AmountHasDust = 20,
+    UpgradeDelayTooShort = 21
 }
@@ -0,0 +1,70 @@
+//! Events published by the BalancedDollar in addition to the standard token
+//! events emitted through `TokenUtils`.
+//!
+//! Every event is published with the topics `(name, EVENT_VERSION)`.
+//!
+//! | name                          | data                   |
+//! |-------------------------------|------------------------|
+//! | `muxed_mint`                  | `(to, mux_id, amount)` |
+//! | `hub_decimals_updated`        | hub decimals           |
+//! | `upgrade_authority_proposed`  | `PendingAddress`       |
+//! | `upgrade_authority_cancelled` | proposed authority     |
+//! | `upgrade_authority_changed`   | new upgrade authority  |
+//! | `upgrade_delay_updated`       | delay                  |
+//! | `upgrade_scheduled`           | `ScheduledUpgrade`     |
+//! | `upgrade_cancelled`           | `(wasm hash, admin)`   |
+//! | `upgraded`                    | new wasm hash          |
+//! | `migrated`                    | `(from, to)` versions  |
+use soroban_sdk::{Address, BytesN, Env, IntoVal, Symbol, Val};
+
+use crate::storage_types::{PendingAddress, ScheduledUpgrade};
+
+pub const EVENT_VERSION: u32 = 1;
+
+fn publish<D>(e: &Env, name: &str, data: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    e.events()
+        .publish((Symbol::new(e, name), EVENT_VERSION), data);
+}
+
+pub(crate) fn muxed_mint(e: &Env, to: Address, mux_id: u64, amount: u128) {
+    publish(e, "muxed_mint", (to, mux_id, amount));
+}
+
+pub(crate) fn hub_decimals_updated(e: &Env, hub_decimals: u32) {
+    publish(e, "hub_decimals_updated", hub_decimals);
+}
+
+pub(crate) fn upgrade_authority_proposed(e: &Env, proposal: PendingAddress) {
+    publish(e, "upgrade_authority_proposed", proposal);
+}
+
+pub(crate) fn upgrade_authority_cancelled(e: &Env, proposed: Address) {
+    publish(e, "upgrade_authority_cancelled", proposed);
+}
+
+pub(crate) fn upgrade_authority_changed(e: &Env, upgrade_authority: Address) {
+    publish(e, "upgrade_authority_changed", upgrade_authority);
+}
+
+pub(crate) fn upgrade_delay_updated(e: &Env, delay: u64) {
+    publish(e, "upgrade_delay_updated", delay);
+}
+
+pub(crate) fn upgrade_scheduled(e: &Env, upgrade: ScheduledUpgrade) {
+    publish(e, "upgrade_scheduled", upgrade);
+}
+
+pub(crate) fn upgrade_cancelled(e: &Env, wasm_hash: BytesN<32>, admin: Address) {
+    publish(e, "upgrade_cancelled", (wasm_hash, admin));
+}
+
+pub(crate) fn upgraded(e: &Env, new_wasm_hash: BytesN<32>) {
+    publish(e, "upgraded", new_wasm_hash);
+}
+
+pub(crate) fn migrated(e: &Env, from: u32, to: u32) {
+    publish(e, "migrated", (from, to));
+}
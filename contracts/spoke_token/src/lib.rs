@@ -5,6 +5,8 @@ mod balance;
 pub mod spoke_token;
 pub mod contract;
 mod errors;
+mod events;
+mod migration;
 mod metadata;
 mod storage_types;
 mod tests;
@@ -0,0 +1,19 @@
+//! Storage migrations applied by `migrate` after an upgrade.
+//!
+//! Contracts deployed before schema versioning was introduced have no stored
+//! version and are treated as version 0. Each step moves the storage from
+//! `version` to `version + 1` and runs exactly once.
+use soroban_sdk::Env;
+
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+pub(crate) fn migrate(_e: &Env, from: u32) {
+    for version in from..SCHEMA_VERSION {
+        match version {
+            // Version 1 only starts recording the schema version, balances,
+            // allowances and metadata kept their shape.
+            0 => {}
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::balance::{receive_balance, spend_balance};
-use crate::storage_types::{get_icon_hub_token, get_xcall, get_xcall_manager, get_xcall_network_address};
+use crate::metadata::read_decimal;
+use crate::storage_types::{get_hub_decimals, get_icon_hub_token, get_xcall, get_xcall_manager, get_xcall_network_address};
 use soroban_sdk::{xdr::ToXdr, Address, Bytes, Env, String, Vec};
 mod xcall {
     soroban_sdk::contractimport!(file = "../../wasm/xcall.wasm");
@@ -7,8 +8,12 @@ mod xcall {
 
 use crate::contract;
 use crate::errors::ContractError;
+use crate::events;
 use crate::xcall_manager_interface::XcallManagerClient;
-use soroban_rlp::balanced::address_utils::is_valid_bytes_address;
+use soroban_rlp::balanced::address_utils::{
+    account_address, decode_bytes_address, get_network_id, strkey::StrKey,
+};
+use soroban_rlp::balanced::decimal_utils::scale_amount;
 use soroban_rlp::balanced::messages::{
     cross_transfer::CrossTransfer, cross_transfer_revert::CrossTransferRevert,
 };
@@ -25,12 +30,18 @@ pub fn _cross_transfer(
     to: String,
     data: Bytes,
 ) -> Result<(), ContractError> {
+    // Dust that can't be represented with the hub decimals is not burned.
+    let (hub_amount, dust) = to_hub_amount(&e, amount)?;
+    let amount = amount - dust;
+    if amount == 0 {
+        return Err(ContractError::InvalidAmount);
+    }
     if amount <= i128::MAX as u128 {
         _burn(&e, from.clone(), amount as i128);
     }else{
         return Err(ContractError::InvalidAmount);
     }
-    let xcall_message = CrossTransfer::new(from.clone().to_string(), to, amount, data);
+    let xcall_message = CrossTransfer::new(from.clone().to_string(), to, hub_amount, data);
     let rollback = CrossTransferRevert::new(from.clone(), amount);
     let icon_bn_usd = get_icon_hub_token(&e)?;
 
@@ -54,6 +65,39 @@ pub fn _cross_transfer(
     Ok(())
 }
 
+pub fn _quote_cross_transfer_fee(e: Env) -> Result<u128, ContractError> {
+    let icon_bn_usd = get_icon_hub_token(&e)?;
+    let nid = get_network_id(&icon_bn_usd).ok_or(ContractError::InvalidNetworkAddress)?;
+    let (sources, _) = xcall_manager_client(&e, &get_xcall_manager(&e)?).get_protocols();
+    Ok(xcall_client(&e, &get_xcall(&e)?).get_fee(&nid, &true, &Some(sources)))
+}
+
+/// Scales a local amount to the hub decimals, returning the scaled amount and
+/// the local dust truncated by the conversion.
+fn to_hub_amount(e: &Env, amount: u128) -> Result<(u128, u128), ContractError> {
+    match get_hub_decimals(e) {
+        Some(hub_decimals) => {
+            scale_amount(amount, read_decimal(e), hub_decimals).ok_or(ContractError::InvalidAmount)
+        }
+        None => Ok((amount, 0)),
+    }
+}
+
+/// Scales a hub amount to the local decimals. Amounts that can't be minted
+/// exactly are rejected rather than truncated.
+fn from_hub_amount(e: &Env, amount: u128) -> Result<u128, ContractError> {
+    let hub_decimals = match get_hub_decimals(e) {
+        Some(hub_decimals) => hub_decimals,
+        None => return Ok(amount),
+    };
+    let (local_amount, dust) =
+        scale_amount(amount, hub_decimals, read_decimal(e)).ok_or(ContractError::InvalidAmount)?;
+    if dust > 0 {
+        return Err(ContractError::AmountHasDust);
+    }
+    Ok(local_amount)
+}
+
 fn verify_protocol(
     e: &Env,
     xcall_manager: &Address,
@@ -82,9 +126,13 @@ pub fn _handle_call_message(
             return Err(ContractError::OnlyIconHubToken);
         }
         let message = CrossTransfer::decode(&e, data);
-        let to_network_address: Address = get_address(message.to, &e)?;
-        if message.amount <= i128::MAX as u128 {
-            _mint(&e, to_network_address, message.amount as i128);
+        let (to_network_address, mux_id) = get_address(message.to, &e)?;
+        let amount = from_hub_amount(&e, message.amount)?;
+        if amount <= i128::MAX as u128 {
+            _mint(&e, to_network_address.clone(), amount as i128);
+            if let Some(mux_id) = mux_id {
+                events::muxed_mint(&e, to_network_address, mux_id, amount);
+            }
         }else{
             return Err(ContractError::InvalidAmount);
         }
@@ -106,7 +154,12 @@ pub fn _handle_call_message(
     Ok(())
 }
 
-pub fn get_address(network_address: String, env: &Env) -> Result<Address, ContractError> {
+/// Returns the account of a `<nid>/<address>` network address. A muxed `M...`
+/// address resolves to its underlying account, returned with its mux id.
+pub fn get_address(
+    network_address: String,
+    env: &Env,
+) -> Result<(Address, Option<u64>), ContractError> {
     let bytes = network_address.to_xdr(&env);
 
     if bytes.get(6).unwrap() > 0 {
@@ -134,10 +187,12 @@ pub fn get_address(network_address: String, env: &Env) -> Result<Address, Contra
         return Err(ContractError::InvalidNetworkAddress);
     }
 
-    if !is_valid_bytes_address(&account) {
-        return Err(ContractError::InvalidAddress);
+    match decode_bytes_address(&account).map_err(|_| ContractError::InvalidAddress)? {
+        StrKey::Muxed { ed25519, id } => {
+            Ok((Address::from_string(&account_address(env, &ed25519)), Some(id)))
+        }
+        _ => Ok((Address::from_string_bytes(&account), None)),
     }
-    Ok(Address::from_string_bytes(&account))
 }
 
 fn _mint(e: &Env, to: Address, amount: i128) {
@@ -158,6 +213,11 @@ pub fn xcall_client(e: &Env, xcall: &Address) -> Client<'static> {
     return xcall::Client::new(e, xcall);
 }
 
+pub fn xcall_manager_admin(e: &Env) -> Address {
+    let xcall_manager = get_xcall_manager(e).unwrap();
+    xcall_manager_client(e, &xcall_manager).get_admin()
+}
+
 fn xcall_manager_client(e: &Env, xcall_manager: &Address) -> XcallManagerClient<'static> {
     let client = XcallManagerClient::new(e, xcall_manager);
     return client;
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use soroban_sdk::{contracttype, Address, BytesN, Env, String};
 
 use crate::errors::ContractError;
 
@@ -9,6 +9,10 @@ pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_I
 pub(crate) const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub(crate) const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
+pub(crate) const PROPOSAL_EXPIRY: u64 = 7 * 86400;
+pub(crate) const DEFAULT_UPGRADE_DELAY: u64 = 2 * 86400;
+pub(crate) const MIN_UPGRADE_DELAY: u64 = 3600;
+
 #[derive(Clone)]
 #[contracttype]
 pub struct AllowanceDataKey {
@@ -32,6 +36,25 @@ pub enum DataKey {
     HubToken,
     UpgradeAuthority,
     XCallNetworkAddress,
+    PendingUpgradeAuthority,
+    ScheduledUpgrade,
+    UpgradeDelay,
+    SchemaVersion,
+    HubDecimals,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingAddress {
+    pub address: Address,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ScheduledUpgrade {
+    pub wasm_hash: BytesN<32>,
+    pub executable_at: u64,
 }
 
 pub fn set_xcall_manager(e: &Env, value: Address) {
@@ -50,6 +73,30 @@ pub fn set_upgrade_authority(e: &Env, value: Address) {
     e.storage().instance().set(&DataKey::UpgradeAuthority, &value);
 }
 
+pub fn set_pending_upgrade_authority(e: &Env, value: PendingAddress) {
+    e.storage().instance().set(&DataKey::PendingUpgradeAuthority, &value);
+}
+
+pub fn remove_pending_upgrade_authority(e: &Env) {
+    e.storage().instance().remove(&DataKey::PendingUpgradeAuthority);
+}
+
+pub fn set_schema_version(e: &Env, value: u32) {
+    e.storage().instance().set(&DataKey::SchemaVersion, &value);
+}
+
+pub fn set_upgrade_delay(e: &Env, value: u64) {
+    e.storage().instance().set(&DataKey::UpgradeDelay, &value);
+}
+
+pub fn set_scheduled_upgrade(e: &Env, value: ScheduledUpgrade) {
+    e.storage().instance().set(&DataKey::ScheduledUpgrade, &value);
+}
+
+pub fn remove_scheduled_upgrade(e: &Env) {
+    e.storage().instance().remove(&DataKey::ScheduledUpgrade);
+}
+
 pub fn set_xcall_network_address(e: &Env, value: String) {
     e.storage().instance().set(&DataKey::XCallNetworkAddress, &value);
 }
@@ -94,6 +141,32 @@ pub fn get_xcall_network_address(e: &Env) -> Result<String, ContractError> {
         .get(&key)
         .ok_or(ContractError::Uninitialized)}
 
+pub fn get_pending_upgrade_authority(e: &Env) -> Option<PendingAddress> {
+    e.storage().instance().get(&DataKey::PendingUpgradeAuthority)
+}
 
+pub fn set_hub_decimals(e: &Env, value: u32) {
+    e.storage().instance().set(&DataKey::HubDecimals, &value);
+}
 
+pub fn get_hub_decimals(e: &Env) -> Option<u32> {
+    e.storage().instance().get(&DataKey::HubDecimals)
+}
 
+pub fn get_upgrade_delay(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::UpgradeDelay)
+        .unwrap_or(DEFAULT_UPGRADE_DELAY)
+}
+
+pub fn get_scheduled_upgrade(e: &Env) -> Option<ScheduledUpgrade> {
+    e.storage().instance().get(&DataKey::ScheduledUpgrade)
+}
+
+pub fn get_schema_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::SchemaVersion)
+        .unwrap_or(0)
+}
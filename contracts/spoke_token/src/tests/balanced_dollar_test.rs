@@ -1,15 +1,24 @@
 #![cfg(test)]
 extern crate std;
 
-use crate::{contract::BalancedDollarClient, storage_types::get_upgrade_authority};
+use crate::{
+    contract::BalancedDollarClient,
+    errors::ContractError,
+    events::EVENT_VERSION,
+    migration::SCHEMA_VERSION,
+    storage_types::{
+        get_upgrade_authority, PendingAddress, ScheduledUpgrade, DEFAULT_UPGRADE_DELAY,
+        PROPOSAL_EXPIRY,
+    },
+};
 
 use super::setup::*;
 use soroban_rlp::balanced::messages::{
     cross_transfer::CrossTransfer, cross_transfer_revert::CrossTransferRevert,
 };
 use soroban_sdk::{
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
-    Address, Bytes, IntoVal, String, Symbol, Vec,
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger},
+    vec, Address, Bytes, IntoVal, String, Symbol, Vec,
 };
 
 #[test]
@@ -348,13 +357,13 @@ fn test_extend_ttl() {
 }
 
 #[test]
-fn test_set_upgrade_authority() {
+fn test_propose_and_accept_upgrade_authority() {
     let ctx = TestContext::default();
     let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
     ctx.init_context(&client);
 
     let new_upgrade_authority = Address::generate(&ctx.env);
-    client.set_upgrade_authority(&new_upgrade_authority);
+    client.propose_upgrade_authority(&new_upgrade_authority);
 
     assert_eq!(
         ctx.env.auths(),
@@ -363,16 +372,414 @@ fn test_set_upgrade_authority() {
             AuthorizedInvocation {
                 function: AuthorizedFunction::Contract((
                     ctx.registry.clone(),
-                    Symbol::new(&ctx.env, "set_upgrade_authority"),
+                    Symbol::new(&ctx.env, "propose_upgrade_authority"),
                     (&new_upgrade_authority,).into_val(&ctx.env)
                 )),
                 sub_invocations: std::vec![]
             }
         )]
     );
+    let proposal = PendingAddress {
+        address: new_upgrade_authority.clone(),
+        expires_at: ctx.env.ledger().timestamp() + PROPOSAL_EXPIRY,
+    };
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "upgrade_authority_proposed"),
+                    EVENT_VERSION
+                )
+                    .into_val(&ctx.env),
+                proposal.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_pending_upgrade_authority(), Some(proposal));
+
+    client.accept_upgrade_authority();
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            new_upgrade_authority.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "accept_upgrade_authority"),
+                    ().into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "upgrade_authority_changed"),
+                    EVENT_VERSION
+                )
+                    .into_val(&ctx.env),
+                new_upgrade_authority.clone().into_val(&ctx.env)
+            )
+        ]
+    );
 
     ctx.env.as_contract(&client.address, || {
         let upgrade_authority = get_upgrade_authority(&ctx.env).unwrap();
         assert_eq!(upgrade_authority, new_upgrade_authority)
     });
+    assert_eq!(client.get_pending_upgrade_authority(), None);
+}
+
+#[test]
+fn test_cancel_upgrade_authority_proposal() {
+    let ctx = TestContext::default();
+    let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let new_upgrade_authority = Address::generate(&ctx.env);
+    client.propose_upgrade_authority(&new_upgrade_authority);
+    client.cancel_upgrade_auth_proposal();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "upgrade_authority_cancelled"),
+                    EVENT_VERSION
+                )
+                    .into_val(&ctx.env),
+                new_upgrade_authority.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+
+    assert_eq!(client.get_pending_upgrade_authority(), None);
+    assert_eq!(
+        client.try_accept_upgrade_authority(),
+        Err(Ok(ContractError::NoPendingProposal))
+    );
+
+    client.propose_upgrade_authority(&new_upgrade_authority);
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PROPOSAL_EXPIRY + 1);
+    assert_eq!(
+        client.try_accept_upgrade_authority(),
+        Err(Ok(ContractError::ProposalExpired))
+    );
+    ctx.env.as_contract(&client.address, || {
+        let upgrade_authority = get_upgrade_authority(&ctx.env).unwrap();
+        assert_eq!(upgrade_authority, ctx.upgrade_authority)
+    });
+}
+
+#[test]
+fn test_schedule_and_execute_upgrade() {
+    let ctx = TestContext::default();
+    let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let wasm_hash = ctx.upload_xcall_manager_wasm();
+    client.schedule_upgrade(&wasm_hash);
+    let upgrade = ScheduledUpgrade {
+        wasm_hash: wasm_hash.clone(),
+        executable_at: ctx.env.ledger().timestamp() + DEFAULT_UPGRADE_DELAY,
+    };
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgrade_scheduled"), EVENT_VERSION).into_val(&ctx.env),
+                upgrade.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_scheduled_upgrade(), Some(upgrade));
+    assert_eq!(
+        client.try_execute_upgrade(),
+        Err(Ok(ContractError::UpgradeLocked))
+    );
+
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += DEFAULT_UPGRADE_DELAY);
+    client.execute_upgrade();
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            ctx.upgrade_authority.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "execute_upgrade"),
+                    ().into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgraded"), EVENT_VERSION).into_val(&ctx.env),
+                wasm_hash.into_val(&ctx.env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_cancel_upgrade() {
+    let ctx = TestContext::default();
+    let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    assert_eq!(
+        client.try_set_upgrade_delay(&3599),
+        Err(Ok(ContractError::UpgradeDelayTooShort))
+    );
+    client.set_upgrade_delay(&3600);
+    assert_eq!(client.get_upgrade_delay(), 3600);
+
+    let wasm_hash = ctx.upload_xcall_manager_wasm();
+    client.schedule_upgrade(&wasm_hash);
+    assert_eq!(
+        client.try_schedule_upgrade(&wasm_hash),
+        Err(Ok(ContractError::UpgradeAlreadyScheduled))
+    );
+
+    client.cancel_upgrade();
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            ctx.admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "cancel_upgrade"),
+                    ().into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgrade_cancelled"), EVENT_VERSION).into_val(&ctx.env),
+                (wasm_hash, ctx.admin.clone()).into_val(&ctx.env)
+            )
+        ]
+    );
+
+    ctx.env.ledger().with_mut(|li| li.timestamp += 3600);
+    assert_eq!(
+        client.try_execute_upgrade(),
+        Err(Ok(ContractError::UpgradeNotScheduled))
+    );
+}
+
+#[test]
+fn test_upgrade_and_migrate_previous_version() {
+    let ctx = TestContext::default();
+    let address = ctx.deploy_previous_version();
+    let previous = balanced_dollar_v0::Client::new(&ctx.env, &address);
+    previous.approve(&ctx.depositor, &ctx.withdrawer, &1000, &1000);
+    previous.upgrade(&ctx.upload_balanced_dollar_wasm());
+
+    let client = BalancedDollarClient::new(&ctx.env, &address);
+    assert_eq!(client.version(), 0);
+    client.migrate();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                address.clone(),
+                (Symbol::new(&ctx.env, "migrated"), EVENT_VERSION).into_val(&ctx.env),
+                (0u32, SCHEMA_VERSION).into_val(&ctx.env)
+            )
+        ]
+    );
+
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert_eq!(client.symbol(), String::from_str(&ctx.env, "bnUSD"));
+    assert_eq!(client.decimals(), 18);
+    assert_eq!(client.xcall_manager(), ctx.xcall_manager);
+    assert_eq!(client.allowance(&ctx.depositor, &ctx.withdrawer), 1000);
+    assert_eq!(
+        client.try_migrate(),
+        Err(Ok(ContractError::AlreadyMigrated))
+    );
+}
+
+#[test]
+fn test_quote_cross_transfer_fee() {
+    let ctx = TestContext::default();
+    let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
+    ctx.env.mock_all_auths();
+    ctx.init_context(&client);
+
+    let hub_nid = String::from_str(&ctx.env, "icon01");
+    ctx.set_connection_fee(&hub_nid, 150, 50);
+
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    let expected = ctx.xcall_client.get_fee(&hub_nid, &true, &Some(sources));
+    assert_eq!(expected, 100 + 150 + 50);
+    assert_eq!(client.quote_cross_transfer_fee(), expected);
+}
+
+#[test]
+fn test_hub_decimals_scale_cross_transfer_amounts() {
+    let ctx = TestContext::default();
+    let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    assert_eq!(client.get_hub_decimals(), None);
+    client.set_hub_decimals(&16);
+    assert_eq!(client.get_hub_decimals(), Some(16));
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "hub_decimals_updated"), EVENT_VERSION).into_val(&ctx.env),
+                16u32.into_val(&ctx.env)
+            )
+        ]
+    );
+
+    let recipient = Address::from_string(&String::from_str(
+        &ctx.env,
+        "CA36FQITV33RO5SJFPTNLRQBD6ZNAEJG7F7J5KWCV4OP7SQHDMIZCT33",
+    ));
+    let data = CrossTransfer::new(
+        String::from_str(&ctx.env, "icon01/hxjkdvhui"),
+        String::from_str(
+            &ctx.env,
+            "stellar/CA36FQITV33RO5SJFPTNLRQBD6ZNAEJG7F7J5KWCV4OP7SQHDMIZCT33",
+        ),
+        1001,
+        Bytes::new(&ctx.env),
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "xCrossTransfer"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.icon_bn_usd, &data, &sources);
+    assert_eq!(client.balance(&recipient), 100100);
+
+    // Dust below the hub precision stays with the sender.
+    ctx.mint_native_token(&recipient, client.quote_cross_transfer_fee());
+    client.cross_transfer(
+        &recipient,
+        &100099,
+        &String::from_str(&ctx.env, "icon01/hxjkdvhui"),
+        &Option::None,
+    );
+    assert_eq!(client.balance(&recipient), 100);
+
+    // Amounts from the hub that can't be minted exactly are rejected.
+    client.set_hub_decimals(&20);
+    assert_eq!(
+        client.try_handle_call_message(&ctx.icon_bn_usd, &data, &sources),
+        Err(Ok(ContractError::AmountHasDust))
+    );
+    assert_eq!(
+        client.try_set_hub_decimals(&60),
+        Err(Ok(ContractError::InvalidDecimals))
+    );
+}
+
+#[test]
+fn test_handle_call_message_for_cross_transfer_to_muxed_address() {
+    let ctx = TestContext::default();
+    let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
+    ctx.env.mock_all_auths();
+    ctx.init_context(&client);
+
+    let muxed = "MAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSAAAAAEPXD6YEZO4WE";
+    let data = CrossTransfer::new(
+        ctx.depositor.to_string(),
+        String::from_str(&ctx.env, &std::format!("stellar/{muxed}")),
+        1000,
+        Bytes::new(&ctx.env),
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "xCrossTransfer"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    client.handle_call_message(&ctx.icon_bn_usd, &data, &sources);
+
+    let account = Address::from_string(&String::from_str(
+        &ctx.env,
+        "GAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSABOV",
+    ));
+    assert_eq!(client.balance(&account), 1000);
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "muxed_mint"), EVENT_VERSION).into_val(&ctx.env),
+                (account, 1234567890123u64, 1000u128).into_val(&ctx.env)
+            )
+        ]
+    );
+
+    // A muxed address with a bad checksum is rejected.
+    let data = CrossTransfer::new(
+        ctx.depositor.to_string(),
+        String::from_str(
+            &ctx.env,
+            "stellar/MAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSAAAAAEPXD6YEZO4WA",
+        ),
+        1000,
+        Bytes::new(&ctx.env),
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "xCrossTransfer"));
+    assert_eq!(
+        client.try_handle_call_message(&ctx.icon_bn_usd, &data, &sources),
+        Err(Ok(ContractError::InvalidAddress))
+    );
+}
+
+#[test]
+fn test_handle_call_message_for_cross_transfer_to_address_with_bad_checksum() {
+    let ctx = TestContext::default();
+    let client = BalancedDollarClient::new(&ctx.env, &ctx.registry);
+    ctx.env.mock_all_auths();
+    ctx.init_context(&client);
+
+    let data = CrossTransfer::new(
+        ctx.depositor.to_string(),
+        String::from_str(
+            &ctx.env,
+            "stellar/GAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQTCQKRMFYYDENBWHA5DYPSABOA",
+        ),
+        1000,
+        Bytes::new(&ctx.env),
+    )
+    .encode(&ctx.env, String::from_str(&ctx.env, "xCrossTransfer"));
+    let sources = Vec::from_array(&ctx.env, [ctx.centralized_connection.to_string()]);
+    assert_eq!(
+        client.try_handle_call_message(&ctx.icon_bn_usd, &data, &sources),
+        Err(Ok(ContractError::InvalidAddress))
+    );
 }
@@ -3,7 +3,10 @@ extern crate std;
 
 use crate::contract::{BalancedDollar, BalancedDollarClient};
 
-use soroban_sdk::{testutils::Address as _, token, Address, Env, String, Vec};
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    token, Address, BytesN, Env, String, Val, Vec,
+};
 
 mod xcall {
     soroban_sdk::contractimport!(file = "../../wasm/xcall.wasm");
@@ -19,6 +22,18 @@ mod xcall_manager {
     );
 }
 
+#[allow(clippy::too_many_arguments)]
+pub mod balanced_dollar_v0 {
+    soroban_sdk::contractimport!(file = "../../wasm/spoke_token_v0.wasm");
+}
+
+#[allow(clippy::too_many_arguments)]
+mod balanced_dollar {
+    soroban_sdk::contractimport!(
+        file = "../../target/wasm32-unknown-unknown/release/spoke_token.wasm"
+    );
+}
+
 use xcall_manager::ConfigData as XcallManagerConfigData;
 
 pub struct TestContext {
@@ -48,6 +63,7 @@ pub struct ConfigData {
 impl TestContext {
     pub fn default() -> Self {
         let env = Env::default();
+        env.budget().reset_unlimited();
         let token_admin = Address::generate(&env);
         let balanced_dollar = env.register_contract(None, BalancedDollar);
         let centralized_connection = env.register_contract_wasm(None, connection::WASM);
@@ -95,6 +111,31 @@ impl TestContext {
         client.initialize(&config.xcall, &config.xcall_manager, &config.icon_bn_usd, &config.upgrade_authority, &name, &symbol, &decimal);
     }
 
+    /// Deploys and initializes the BalancedDollar as it was released before
+    /// schema versioning, to test upgrades and migrations against it.
+    pub fn deploy_previous_version(&self) -> Address {
+        self.env.mock_all_auths();
+        self.init_xcall_manager_context();
+        self.init_xcall_state();
+        let address = self
+            .env
+            .register_contract_wasm(None, balanced_dollar_v0::WASM);
+        balanced_dollar_v0::Client::new(&self.env, &address).initialize(
+            &self.xcall,
+            &self.xcall_manager,
+            &self.icon_bn_usd,
+            &self.upgrade_authority,
+            &String::from_str(&self.env, "Balanced Dollar"),
+            &String::from_str(&self.env, "bnUSD"),
+            &18,
+        );
+        address
+    }
+
+    pub fn upload_balanced_dollar_wasm(&self) -> BytesN<32> {
+        self.env.deployer().upload_contract_wasm(balanced_dollar::WASM)
+    }
+
     pub fn init_xcall_manager_context(&self) {
         let client = self::xcall_manager::Client::new(&self.env, &self.xcall_manager);
         let config = XcallManagerConfigData {
@@ -143,6 +184,11 @@ impl TestContext {
         connection_client.set_fee(&self.nid, &message_fee, &response_fee);
     }
 
+    pub fn set_connection_fee(&self, nid: &String, message_fee: u128, response_fee: u128) {
+        let connection_client = connection::Client::new(&self.env, &self.centralized_connection);
+        connection_client.set_fee(nid, &message_fee, &response_fee);
+    }
+
     pub fn mint_native_token(&self, address: &Address, amount: u128) {
         let native_token_client = token::StellarAssetClient::new(&self.env, &self.native_token);
         native_token_client.mint(&address, &(*&amount as i128));
@@ -154,4 +200,12 @@ impl TestContext {
 
         *&balance as u128
     }
+    pub fn upload_xcall_manager_wasm(&self) -> BytesN<32> {
+        self.env.deployer().upload_contract_wasm(xcall_manager::WASM)
+    }
+
+    pub fn last_event(&self) -> Vec<(Address, Vec<Val>, Val)> {
+        let events = self.env.events().all();
+        events.slice(events.len() - 1..)
+    }
 }
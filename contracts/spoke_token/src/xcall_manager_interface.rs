@@ -1,4 +1,4 @@
-use soroban_sdk::{contractclient, Env, String, Vec};
+use soroban_sdk::{contractclient, Address, Env, String, Vec};
 
 use crate::errors::ContractError;
 
@@ -7,4 +7,6 @@ pub trait IXcallManager {
     fn verify_protocols(e: Env, protocols: Vec<String>) -> Result<bool, ContractError>;
 
     fn get_protocols(e: Env) -> Result<(Vec<String>, Vec<String>), ContractError>;
+
+    fn get_admin(e: Env) -> Address;
 }
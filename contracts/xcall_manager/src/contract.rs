@@ -7,12 +7,18 @@ mod xcall {
 
 use crate::{
     config::{self, get_config, set_config, ConfigData},
+    events, migration,
     states::{
         extend_ttl, has_proposed_removed, has_registry, read_administrator, read_destinations,
-        read_proposed_removed, read_sources, write_administrator, write_destinations,
-        write_proposed_removed, write_registry, write_sources,
+        read_proposal, read_proposed_removed, read_scheduled_upgrade, read_schema_version,
+        read_sources, read_upgrade_delay, remove_proposal, remove_scheduled_upgrade,
+        write_administrator, write_destinations, write_proposal, write_proposed_removed,
+        write_registry, write_scheduled_upgrade, write_schema_version, write_sources,
+        write_upgrade_delay,
+    },
+    storage_types::{
+        DataKey, PendingAddress, ScheduledUpgrade, MIN_UPGRADE_DELAY, PROPOSAL_EXPIRY,
     },
-    storage_types::DataKey,
     white_list_actions::WhiteListActions,
 };
 use soroban_rlp::balanced::messages::configure_protocols::ConfigureProtocols;
@@ -39,6 +45,7 @@ impl XcallManager {
         }
         write_registry(&env, &registry);
         write_administrator(&env, &admin);
+        write_schema_version(&env, migration::SCHEMA_VERSION);
         set_config(&env, config);
         write_sources(&env, &sources);
         write_destinations(&env, &destinations);
@@ -48,11 +55,36 @@ impl XcallManager {
         get_config(&env)
     }
 
-    pub fn set_admin(e: Env, new_admin: Address) {
+    pub fn propose_admin(e: Env, new_admin: Address) {
         let admin = read_administrator(&e);
         admin.require_auth();
 
-        write_administrator(&e, &new_admin);
+        let proposal = Self::new_proposal(&e, new_admin);
+        write_proposal(&e, DataKey::PendingAdmin, &proposal);
+        events::admin_proposed(&e, proposal);
+    }
+
+    pub fn accept_admin(e: Env) -> Result<(), ContractError> {
+        let proposal = Self::take_proposal(&e, DataKey::PendingAdmin)?;
+
+        write_administrator(&e, &proposal.address);
+        events::admin_changed(&e, proposal.address);
+        Ok(())
+    }
+
+    pub fn cancel_admin_proposal(e: Env) -> Result<(), ContractError> {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        let proposal =
+            read_proposal(&e, DataKey::PendingAdmin).ok_or(ContractError::NoPendingProposal)?;
+        remove_proposal(&e, DataKey::PendingAdmin);
+        events::admin_proposal_cancelled(&e, proposal.address);
+        Ok(())
+    }
+
+    pub fn get_pending_admin(e: Env) -> Option<PendingAddress> {
+        read_proposal(&e, DataKey::PendingAdmin)
     }
 
     pub fn get_admin(e: Env) -> Address {
@@ -195,20 +227,138 @@ impl XcallManager {
         return Ok(new_array);
     }
 
-    pub fn set_upgrade_authority(e: Env, upgrade_authority: Address) {
+    pub fn propose_upgrade_authority(e: Env, upgrade_authority: Address) {
+        let config = config::get_config(&e);
+        config.upgrade_authority.require_auth();
+
+        let proposal = Self::new_proposal(&e, upgrade_authority);
+        write_proposal(&e, DataKey::PendingUpgradeAuthority, &proposal);
+        events::upgrade_authority_proposed(&e, proposal);
+    }
+
+    pub fn accept_upgrade_authority(e: Env) -> Result<(), ContractError> {
+        let proposal = Self::take_proposal(&e, DataKey::PendingUpgradeAuthority)?;
+
         let mut config = config::get_config(&e);
+        config.upgrade_authority = proposal.address.clone();
+        config::set_config(&e, config);
+        events::upgrade_authority_changed(&e, proposal.address);
+        Ok(())
+    }
 
+    pub fn cancel_upgrade_auth_proposal(e: Env) -> Result<(), ContractError> {
+        let config = config::get_config(&e);
         config.upgrade_authority.require_auth();
 
-        config.upgrade_authority = upgrade_authority;
-        config::set_config(&e, config);
+        let proposal = read_proposal(&e, DataKey::PendingUpgradeAuthority)
+            .ok_or(ContractError::NoPendingProposal)?;
+        remove_proposal(&e, DataKey::PendingUpgradeAuthority);
+        events::upgrade_authority_cancelled(&e, proposal.address);
+        Ok(())
+    }
+
+    pub fn get_pending_upgrade_authority(e: Env) -> Option<PendingAddress> {
+        read_proposal(&e, DataKey::PendingUpgradeAuthority)
+    }
+
+    fn new_proposal(e: &Env, address: Address) -> PendingAddress {
+        PendingAddress {
+            address,
+            expires_at: e.ledger().timestamp() + PROPOSAL_EXPIRY,
+        }
+    }
+
+    fn take_proposal(e: &Env, key: DataKey) -> Result<PendingAddress, ContractError> {
+        let proposal = read_proposal(e, key.clone()).ok_or(ContractError::NoPendingProposal)?;
+        if e.ledger().timestamp() > proposal.expires_at {
+            return Err(ContractError::ProposalExpired);
+        }
+        proposal.address.require_auth();
+
+        remove_proposal(e, key);
+        Ok(proposal)
+    }
+
+    /// Sets how long a scheduled upgrade has to wait before it can be applied,
+    /// at least `MIN_UPGRADE_DELAY`.
+    pub fn set_upgrade_delay(e: Env, delay: u64) -> Result<(), ContractError> {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+        if delay < MIN_UPGRADE_DELAY {
+            return Err(ContractError::UpgradeDelayTooShort);
+        }
+
+        write_upgrade_delay(&e, delay);
+        events::upgrade_delay_updated(&e, delay);
+        Ok(())
+    }
+
+    pub fn get_upgrade_delay(e: Env) -> u64 {
+        read_upgrade_delay(&e)
+    }
+
+    pub fn get_scheduled_upgrade(e: Env) -> Option<ScheduledUpgrade> {
+        read_scheduled_upgrade(&e)
+    }
+
+    pub fn schedule_upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        let config = get_config(&e);
+        config.upgrade_authority.require_auth();
+
+        if read_scheduled_upgrade(&e).is_some() {
+            return Err(ContractError::UpgradeAlreadyScheduled);
+        }
+        let upgrade = ScheduledUpgrade {
+            wasm_hash: new_wasm_hash,
+            executable_at: e.ledger().timestamp() + read_upgrade_delay(&e),
+        };
+        write_scheduled_upgrade(&e, &upgrade);
+        events::upgrade_scheduled(&e, upgrade);
+        Ok(())
+    }
+
+    pub fn execute_upgrade(e: Env) -> Result<(), ContractError> {
+        let config = get_config(&e);
+        config.upgrade_authority.require_auth();
+
+        let upgrade = read_scheduled_upgrade(&e).ok_or(ContractError::UpgradeNotScheduled)?;
+        if e.ledger().timestamp() < upgrade.executable_at {
+            return Err(ContractError::UpgradeLocked);
+        }
+        remove_scheduled_upgrade(&e);
+
+        e.deployer()
+            .update_current_contract_wasm(upgrade.wasm_hash.clone());
+        events::upgraded(&e, upgrade.wasm_hash);
+        Ok(())
     }
 
-    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+    pub fn cancel_upgrade(e: Env) -> Result<(), ContractError> {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        let upgrade = read_scheduled_upgrade(&e).ok_or(ContractError::UpgradeNotScheduled)?;
+        remove_scheduled_upgrade(&e);
+        events::upgrade_cancelled(&e, upgrade.wasm_hash, admin);
+        Ok(())
+    }
+
+    pub fn version(e: Env) -> u32 {
+        read_schema_version(&e)
+    }
+
+    pub fn migrate(e: Env) -> Result<(), ContractError> {
         let config = get_config(&e);
         config.upgrade_authority.require_auth();
 
-        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        let from = read_schema_version(&e);
+        if from >= migration::SCHEMA_VERSION {
+            return Err(ContractError::AlreadyMigrated);
+        }
+        migration::migrate(&e, from);
+        write_schema_version(&e, migration::SCHEMA_VERSION);
+        events::migrated(&e, from, migration::SCHEMA_VERSION);
+        Ok(())
     }
 
     pub fn extend_ttl(e: Env) {
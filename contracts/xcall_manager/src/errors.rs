@@ -14,4 +14,11 @@ pub enum ContractError {
     AdminRequired = 11,
     NoProposalForRemovalExists = 12,
     NotWhiteListed = 13,
+    NoPendingProposal = 14,
+    ProposalExpired = 15,
+    UpgradeAlreadyScheduled = 16,
+    UpgradeNotScheduled = 17,
+    UpgradeLocked = 18,
+    AlreadyMigrated = 19,
+    UpgradeDelayTooShort = 20,
 }
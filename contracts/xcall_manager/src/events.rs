@@ -0,0 +1,75 @@
+//! Events published by the XcallManager.
+//!
+//! Every event is published with the topics `(name, EVENT_VERSION)` so that
+//! indexers can detect shape changes the same way as for the AssetManager.
+//!
+//! | name                          | data                  |
+//! |-------------------------------|-----------------------|
+//! | `admin_proposed`              | `PendingAddress`      |
+//! | `admin_proposal_cancelled`    | proposed admin        |
+//! | `admin_changed`               | new admin             |
+//! | `upgrade_authority_proposed`  | `PendingAddress`      |
+//! | `upgrade_authority_cancelled` | proposed authority    |
+//! | `upgrade_authority_changed`   | new upgrade authority |
+//! | `upgrade_delay_updated`       | delay                 |
+//! | `upgrade_scheduled`           | `ScheduledUpgrade`    |
+//! | `upgrade_cancelled`           | `(wasm hash, admin)`  |
+//! | `upgraded`                    | new wasm hash         |
+//! | `migrated`                    | `(from, to)` versions |
+use soroban_sdk::{Address, BytesN, Env, IntoVal, Symbol, Val};
+
+use crate::storage_types::{PendingAddress, ScheduledUpgrade};
+
+pub const EVENT_VERSION: u32 = 1;
+
+fn publish<D>(e: &Env, name: &str, data: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    e.events()
+        .publish((Symbol::new(e, name), EVENT_VERSION), data);
+}
+
+pub(crate) fn admin_proposed(e: &Env, proposal: PendingAddress) {
+    publish(e, "admin_proposed", proposal);
+}
+
+pub(crate) fn admin_proposal_cancelled(e: &Env, proposed: Address) {
+    publish(e, "admin_proposal_cancelled", proposed);
+}
+
+pub(crate) fn admin_changed(e: &Env, new_admin: Address) {
+    publish(e, "admin_changed", new_admin);
+}
+
+pub(crate) fn upgrade_authority_proposed(e: &Env, proposal: PendingAddress) {
+    publish(e, "upgrade_authority_proposed", proposal);
+}
+
+pub(crate) fn upgrade_authority_cancelled(e: &Env, proposed: Address) {
+    publish(e, "upgrade_authority_cancelled", proposed);
+}
+
+pub(crate) fn upgrade_authority_changed(e: &Env, upgrade_authority: Address) {
+    publish(e, "upgrade_authority_changed", upgrade_authority);
+}
+
+pub(crate) fn upgrade_delay_updated(e: &Env, delay: u64) {
+    publish(e, "upgrade_delay_updated", delay);
+}
+
+pub(crate) fn upgrade_scheduled(e: &Env, upgrade: ScheduledUpgrade) {
+    publish(e, "upgrade_scheduled", upgrade);
+}
+
+pub(crate) fn upgrade_cancelled(e: &Env, wasm_hash: BytesN<32>, admin: Address) {
+    publish(e, "upgrade_cancelled", (wasm_hash, admin));
+}
+
+pub(crate) fn upgraded(e: &Env, new_wasm_hash: BytesN<32>) {
+    publish(e, "upgraded", new_wasm_hash);
+}
+
+pub(crate) fn migrated(e: &Env, from: u32, to: u32) {
+    publish(e, "migrated", (from, to));
+}
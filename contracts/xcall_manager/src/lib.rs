@@ -2,6 +2,8 @@
 mod config;
 pub mod contract;
 mod errors;
+mod events;
+mod migration;
 mod states;
 mod storage_types;
 mod tests;
@@ -1,6 +1,6 @@
 use soroban_sdk::{Address, Env, String, Vec};
 
-use crate::storage_types::DataKey;
+use crate::storage_types::{DataKey, PendingAddress, ScheduledUpgrade, DEFAULT_UPGRADE_DELAY};
 
 pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
 pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
@@ -16,6 +16,50 @@ pub fn write_administrator(e: &Env, id: &Address) {
     e.storage().instance().set(&key, id);
 }
 
+pub fn read_proposal(e: &Env, key: DataKey) -> Option<PendingAddress> {
+    e.storage().instance().get(&key)
+}
+
+pub fn write_proposal(e: &Env, key: DataKey, proposal: &PendingAddress) {
+    e.storage().instance().set(&key, proposal);
+}
+
+pub fn remove_proposal(e: &Env, key: DataKey) {
+    e.storage().instance().remove(&key);
+}
+
+pub fn read_schema_version(e: &Env) -> u32 {
+    let key = DataKey::SchemaVersion;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_schema_version(e: &Env, version: u32) {
+    let key = DataKey::SchemaVersion;
+    e.storage().instance().set(&key, &version);
+}
+
+pub fn read_upgrade_delay(e: &Env) -> u64 {
+    let key = DataKey::UpgradeDelay;
+    e.storage().instance().get(&key).unwrap_or(DEFAULT_UPGRADE_DELAY)
+}
+
+pub fn write_upgrade_delay(e: &Env, delay: u64) {
+    let key = DataKey::UpgradeDelay;
+    e.storage().instance().set(&key, &delay);
+}
+
+pub fn read_scheduled_upgrade(e: &Env) -> Option<ScheduledUpgrade> {
+    e.storage().instance().get(&DataKey::ScheduledUpgrade)
+}
+
+pub fn write_scheduled_upgrade(e: &Env, upgrade: &ScheduledUpgrade) {
+    e.storage().instance().set(&DataKey::ScheduledUpgrade, upgrade);
+}
+
+pub fn remove_scheduled_upgrade(e: &Env) {
+    e.storage().instance().remove(&DataKey::ScheduledUpgrade);
+}
+
 pub fn has_registry(env:Env) -> bool {
     env.storage().instance().has(&DataKey::Registry)
 }
@@ -1,4 +1,8 @@
-use soroban_sdk::contracttype;
+use soroban_sdk::{contracttype, Address, BytesN};
+
+pub(crate) const PROPOSAL_EXPIRY: u64 = 7 * 86400;
+pub(crate) const DEFAULT_UPGRADE_DELAY: u64 = 2 * 86400;
+pub(crate) const MIN_UPGRADE_DELAY: u64 = 3600;
 
 #[derive(Clone)]
 #[contracttype]
@@ -9,5 +13,24 @@ pub enum DataKey {
     Config,
     Sources,
     Destinations,
-    WhiteListedActions
+    WhiteListedActions,
+    PendingAdmin,
+    PendingUpgradeAuthority,
+    ScheduledUpgrade,
+    UpgradeDelay,
+    SchemaVersion
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingAddress {
+    pub address: Address,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ScheduledUpgrade {
+    pub wasm_hash: BytesN<32>,
+    pub executable_at: u64,
 }
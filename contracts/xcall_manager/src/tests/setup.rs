@@ -5,8 +5,11 @@ use crate::contract::{XcallManager, XcallManagerClient};
 
 use crate::config::ConfigData;
 
-use soroban_sdk::Vec;
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    Address, BytesN, Env, String,
+};
+use soroban_sdk::{Val, Vec};
 
 mod xcall {
     soroban_sdk::contractimport!(file = "../../wasm/xcall.wasm");
@@ -16,6 +19,16 @@ mod connection {
     soroban_sdk::contractimport!(file = "../../wasm/centralized_connection.wasm");
 }
 
+pub mod xcall_manager_v0 {
+    soroban_sdk::contractimport!(file = "../../wasm/xcall_manager_v0.wasm");
+}
+
+mod xcall_manager {
+    soroban_sdk::contractimport!(
+        file = "../../target/wasm32-unknown-unknown/release/xcall_manager.wasm"
+    );
+}
+
 pub struct TestContext {
     pub env: Env,
     pub registry: Address,
@@ -34,6 +47,7 @@ pub struct TestContext {
 impl TestContext {
     pub fn default() -> Self {
         let env = Env::default();
+        env.budget().reset_unlimited();
         let token_admin = Address::generate(&env);
         let token = env.register_stellar_asset_contract_v2(token_admin.clone());
         let xcall_manager = env.register_contract(None, XcallManager);
@@ -81,6 +95,31 @@ impl TestContext {
         self.init_xcall_state();
     }
 
+    /// Deploys and initializes the XcallManager as it was released before
+    /// schema versioning, to test upgrades and migrations against it.
+    pub fn deploy_previous_version(&self) -> Address {
+        self.env.mock_all_auths();
+        let address = self
+            .env
+            .register_contract_wasm(None, xcall_manager_v0::WASM);
+        let config = xcall_manager_v0::ConfigData {
+            xcall: self.xcall.clone(),
+            icon_governance: self.icon_governance.clone(),
+            upgrade_authority: self.upgrade_authority.clone(),
+        };
+        let sources = Vec::from_array(&self.env, [self.centralized_connection.to_string()]);
+        let destinations =
+            Vec::from_array(&self.env, [String::from_str(&self.env, "icon/address")]);
+        xcall_manager_v0::Client::new(&self.env, &address).initialize(
+            &address,
+            &self.admin,
+            &config,
+            &sources,
+            &destinations,
+        );
+        address
+    }
+
     pub fn init_xcall_state(&self) {
         let xcall_client = xcall::Client::new(&self.env, &self.xcall);
 
@@ -109,4 +148,18 @@ impl TestContext {
         let response_fee = 100;
         connection_client.set_fee(&self.nid, &message_fee, &response_fee);
     }
+    pub fn upload_xcall_manager_wasm(&self) -> BytesN<32> {
+        self.env
+            .deployer()
+            .upload_contract_wasm(xcall_manager::WASM)
+    }
+
+    pub fn upload_xcall_wasm(&self) -> BytesN<32> {
+        self.env.deployer().upload_contract_wasm(xcall::WASM)
+    }
+
+    pub fn last_event(&self) -> Vec<(Address, Vec<Val>, Val)> {
+        let events = self.env.events().all();
+        events.slice(events.len() - 1..)
+    }
 }
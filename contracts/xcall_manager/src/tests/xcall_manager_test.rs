@@ -1,14 +1,20 @@
 #![cfg(test)]
 extern crate std;
 
-use crate::{config, contract::XcallManagerClient};
+use crate::{
+    config,
+    contract::XcallManagerClient,
+    errors::ContractError,
+    events::EVENT_VERSION,
+    migration::SCHEMA_VERSION,
+    storage_types::{PendingAddress, ScheduledUpgrade, DEFAULT_UPGRADE_DELAY, PROPOSAL_EXPIRY},
+};
 
 use super::setup::*;
 use soroban_rlp::balanced::messages::configure_protocols::ConfigureProtocols;
 use soroban_sdk::{
-    symbol_short,
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
-    vec, Address, IntoVal, String, Symbol, Vec,
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger},
+    vec, Address, Bytes, IntoVal, String, Symbol, Vec,
 };
 
 #[test]
@@ -26,13 +32,13 @@ fn test_initialize() {
 }
 
 #[test]
-fn test_set_admin() {
+fn test_propose_and_accept_admin() {
     let ctx = TestContext::default();
     let client = XcallManagerClient::new(&ctx.env, &ctx.registry);
     ctx.init_context(&client);
 
     let new_admin: Address = Address::generate(&ctx.env);
-    client.set_admin(&new_admin);
+    client.propose_admin(&new_admin);
     assert_eq!(
         ctx.env.auths(),
         std::vec![(
@@ -40,14 +46,101 @@ fn test_set_admin() {
             AuthorizedInvocation {
                 function: AuthorizedFunction::Contract((
                     ctx.registry.clone(),
-                    symbol_short!("set_admin"),
+                    Symbol::new(&ctx.env, "propose_admin"),
                     (&new_admin,).into_val(&ctx.env)
                 )),
                 sub_invocations: std::vec![]
             }
         )]
     );
+    let proposal = PendingAddress {
+        address: new_admin.clone(),
+        expires_at: ctx.env.ledger().timestamp() + PROPOSAL_EXPIRY,
+    };
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "admin_proposed"), EVENT_VERSION).into_val(&ctx.env),
+                proposal.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_admin(), ctx.admin);
+    assert_eq!(client.get_pending_admin(), Some(proposal));
+
+    client.accept_admin();
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            new_admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "accept_admin"),
+                    ().into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "admin_changed"), EVENT_VERSION).into_val(&ctx.env),
+                new_admin.clone().into_val(&ctx.env)
+            )
+        ]
+    );
     assert_eq!(client.get_admin(), new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+}
+
+#[test]
+fn test_cancel_admin_proposal() {
+    let ctx = TestContext::default();
+    let client = XcallManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let new_admin: Address = Address::generate(&ctx.env);
+    client.propose_admin(&new_admin);
+    client.cancel_admin_proposal();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "admin_proposal_cancelled"),
+                    EVENT_VERSION
+                )
+                    .into_val(&ctx.env),
+                new_admin.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(ContractError::NoPendingProposal))
+    );
+
+    client.propose_admin(&new_admin);
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PROPOSAL_EXPIRY + 1);
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(ContractError::ProposalExpired))
+    );
+    assert_eq!(client.get_admin(), ctx.admin);
 }
 
 #[test]
@@ -337,7 +430,6 @@ fn test_handle_call_message_for_configure_protocols_panic_for_only_icon_governan
     assert_eq!(d, destinations);
 }
 
-
 #[test]
 #[should_panic(expected = "HostError: Error(Contract, #10)")]
 fn test_handle_call_message_for_configure_protocols_panic_for_unknown_mesage_type() {
@@ -384,13 +476,13 @@ fn test_extend_ttl() {
 }
 
 #[test]
-fn test_set_upgrade_authority() {
+fn test_propose_and_accept_upgrade_authority() {
     let ctx = TestContext::default();
     let client = XcallManagerClient::new(&ctx.env, &ctx.registry);
     ctx.init_context(&client);
 
     let new_upgrade_authority = Address::generate(&ctx.env);
-    client.set_upgrade_authority(&new_upgrade_authority);
+    client.propose_upgrade_authority(&new_upgrade_authority);
 
     assert_eq!(
         ctx.env.auths(),
@@ -399,16 +491,216 @@ fn test_set_upgrade_authority() {
             AuthorizedInvocation {
                 function: AuthorizedFunction::Contract((
                     ctx.registry.clone(),
-                    Symbol::new(&ctx.env, "set_upgrade_authority"),
+                    Symbol::new(&ctx.env, "propose_upgrade_authority"),
                     (&new_upgrade_authority,).into_val(&ctx.env)
                 )),
                 sub_invocations: std::vec![]
             }
         )]
     );
+    let proposal = PendingAddress {
+        address: new_upgrade_authority.clone(),
+        expires_at: ctx.env.ledger().timestamp() + PROPOSAL_EXPIRY,
+    };
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "upgrade_authority_proposed"),
+                    EVENT_VERSION
+                )
+                    .into_val(&ctx.env),
+                proposal.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_pending_upgrade_authority(), Some(proposal));
 
+    client.accept_upgrade_authority();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "upgrade_authority_changed"),
+                    EVENT_VERSION
+                )
+                    .into_val(&ctx.env),
+                new_upgrade_authority.clone().into_val(&ctx.env)
+            )
+        ]
+    );
     ctx.env.as_contract(&client.address, || {
         let config = config::get_config(&ctx.env);
         assert_eq!(config.upgrade_authority, new_upgrade_authority)
     });
+    assert_eq!(client.get_pending_upgrade_authority(), None);
+    assert_eq!(
+        client.try_cancel_upgrade_auth_proposal(),
+        Err(Ok(ContractError::NoPendingProposal))
+    );
+
+    let other_authority = Address::generate(&ctx.env);
+    client.propose_upgrade_authority(&other_authority);
+    client.cancel_upgrade_auth_proposal();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (
+                    Symbol::new(&ctx.env, "upgrade_authority_cancelled"),
+                    EVENT_VERSION
+                )
+                    .into_val(&ctx.env),
+                other_authority.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_pending_upgrade_authority(), None);
+}
+
+#[test]
+fn test_schedule_and_execute_upgrade() {
+    let ctx = TestContext::default();
+    let client = XcallManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    let wasm_hash = ctx.upload_xcall_wasm();
+    client.schedule_upgrade(&wasm_hash);
+    let upgrade = ScheduledUpgrade {
+        wasm_hash: wasm_hash.clone(),
+        executable_at: ctx.env.ledger().timestamp() + DEFAULT_UPGRADE_DELAY,
+    };
+    assert_eq!(
+        ctx.env.auths(),
+        std::vec![(
+            ctx.upgrade_authority.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    ctx.registry.clone(),
+                    Symbol::new(&ctx.env, "schedule_upgrade"),
+                    (wasm_hash.clone(),).into_val(&ctx.env)
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgrade_scheduled"), EVENT_VERSION).into_val(&ctx.env),
+                upgrade.clone().into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_scheduled_upgrade(), Some(upgrade));
+    assert_eq!(
+        client.try_execute_upgrade(),
+        Err(Ok(ContractError::UpgradeLocked))
+    );
+
+    ctx.env
+        .ledger()
+        .with_mut(|li| li.timestamp += DEFAULT_UPGRADE_DELAY);
+    client.execute_upgrade();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgraded"), EVENT_VERSION).into_val(&ctx.env),
+                wasm_hash.into_val(&ctx.env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_cancel_upgrade() {
+    let ctx = TestContext::default();
+    let client = XcallManagerClient::new(&ctx.env, &ctx.registry);
+    ctx.init_context(&client);
+
+    assert_eq!(
+        client.try_set_upgrade_delay(&3599),
+        Err(Ok(ContractError::UpgradeDelayTooShort))
+    );
+    client.set_upgrade_delay(&3600);
+    assert_eq!(client.get_upgrade_delay(), 3600);
+
+    let wasm_hash = ctx.upload_xcall_wasm();
+    client.schedule_upgrade(&wasm_hash);
+    client.cancel_upgrade();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                ctx.registry.clone(),
+                (Symbol::new(&ctx.env, "upgrade_cancelled"), EVENT_VERSION).into_val(&ctx.env),
+                (wasm_hash, ctx.admin.clone()).into_val(&ctx.env)
+            )
+        ]
+    );
+    assert_eq!(client.get_scheduled_upgrade(), None);
+
+    ctx.env.ledger().with_mut(|li| li.timestamp += 3600);
+    assert_eq!(
+        client.try_execute_upgrade(),
+        Err(Ok(ContractError::UpgradeNotScheduled))
+    );
+}
+
+#[test]
+fn test_upgrade_and_migrate_previous_version() {
+    let ctx = TestContext::default();
+    let address = ctx.deploy_previous_version();
+    let previous = xcall_manager_v0::Client::new(&ctx.env, &address);
+    let action = Bytes::from_array(&ctx.env, &[1, 2, 3]);
+    previous.white_list_actions(&action);
+    previous.upgrade(&ctx.upload_xcall_manager_wasm());
+
+    let client = XcallManagerClient::new(&ctx.env, &address);
+    assert_eq!(client.version(), 0);
+    client.migrate();
+    assert_eq!(
+        ctx.last_event(),
+        vec![
+            &ctx.env,
+            (
+                address.clone(),
+                (Symbol::new(&ctx.env, "migrated"), EVENT_VERSION).into_val(&ctx.env),
+                (0u32, SCHEMA_VERSION).into_val(&ctx.env)
+            )
+        ]
+    );
+
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert_eq!(client.get_admin(), ctx.admin);
+    assert_eq!(client.get_config().upgrade_authority, ctx.upgrade_authority);
+    let (sources, destinations) = client.get_protocols();
+    assert_eq!(
+        sources,
+        vec![&ctx.env, ctx.centralized_connection.to_string()]
+    );
+    assert_eq!(
+        destinations,
+        vec![&ctx.env, String::from_str(&ctx.env, "icon/address")]
+    );
+    assert!(client.remove_action(&action));
+    assert_eq!(
+        client.try_migrate(),
+        Err(Ok(ContractError::AlreadyMigrated))
+    );
 }
@@ -0,0 +1,71 @@
+use soroban_sdk::{Bytes, Env, String};
+
+pub mod strkey;
+
+use strkey::{StrKey, StrKeyError, MUXED_ADDRESS_LENGTH};
+
+/// Returns whether `address` is a valid `G...` account or `C...` contract
+/// address.
+pub fn is_valid_string_address(address: &String) -> bool {
+    matches!(
+        decode_string_address(address),
+        Ok(StrKey::Account(_) | StrKey::Contract(_))
+    )
+}
+
+/// Returns whether `address` is a valid `G...` account or `C...` contract
+/// address.
+pub fn is_valid_bytes_address(address: &Bytes) -> bool {
+    matches!(
+        decode_bytes_address(address),
+        Ok(StrKey::Account(_) | StrKey::Contract(_))
+    )
+}
+
+/// Decodes a StrKey address, verifying its version byte and checksum.
+pub fn decode_string_address(address: &String) -> Result<StrKey, StrKeyError> {
+    let len = address.len() as usize;
+    if len > MUXED_ADDRESS_LENGTH {
+        return Err(StrKeyError::InvalidLength);
+    }
+    let mut buffer = [0u8; MUXED_ADDRESS_LENGTH];
+    address.copy_into_slice(&mut buffer[..len]);
+    strkey::decode(&buffer[..len])
+}
+
+/// Decodes a StrKey address given as bytes, verifying its version byte and
+/// checksum.
+pub fn decode_bytes_address(address: &Bytes) -> Result<StrKey, StrKeyError> {
+    let len = address.len() as usize;
+    if len > MUXED_ADDRESS_LENGTH {
+        return Err(StrKeyError::InvalidLength);
+    }
+    let mut buffer = [0u8; MUXED_ADDRESS_LENGTH];
+    address.copy_into_slice(&mut buffer[..len]);
+    strkey::decode(&buffer[..len])
+}
+
+/// Returns the `G...` address of the account with the ed25519 key `ed25519`,
+/// e.g. the account underlying a muxed address.
+pub fn account_address(env: &Env, ed25519: &[u8; 32]) -> String {
+    String::from_bytes(env, &strkey::encode_account(ed25519))
+}
+
+/// Returns the network id part of a `<nid>/<account>` network address.
+pub fn get_network_id(network_address: &String) -> Option<String> {
+    let len = network_address.len() as usize;
+    let mut buffer = [0u8; 256];
+    if len > buffer.len() {
+        return None;
+    }
+    network_address.copy_into_slice(&mut buffer[..len]);
+
+    let separator = buffer[..len].iter().position(|&byte| byte == b'/')?;
+    if separator == 0 {
+        return None;
+    }
+    Some(String::from_bytes(
+        network_address.env(),
+        &buffer[..separator],
+    ))
+}
@@ -0,0 +1,250 @@
+//! StrKey decoding of Stellar account (`G...`), contract (`C...`) and muxed
+//! account (`M...`) addresses.
+//!
+//! A StrKey is the base32 encoding, without padding, of a version byte, the
+//! payload and a CRC16-XModem checksum of both, stored little endian.
+
+pub const ACCOUNT_ADDRESS_LENGTH: usize = 56;
+pub const MUXED_ADDRESS_LENGTH: usize = 69;
+
+const ACCOUNT_VERSION_BYTE: u8 = 6 << 3;
+const CONTRACT_VERSION_BYTE: u8 = 2 << 3;
+const MUXED_VERSION_BYTE: u8 = 12 << 3;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrKeyError {
+    InvalidLength,
+    InvalidCharacter,
+    InvalidPadding,
+    InvalidVersionByte,
+    InvalidChecksum,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrKey {
+    Account([u8; 32]),
+    Contract([u8; 32]),
+    Muxed { ed25519: [u8; 32], id: u64 },
+}
+
+/// Decodes and validates a StrKey address.
+pub fn decode(address: &[u8]) -> Result<StrKey, StrKeyError> {
+    match address.len() {
+        ACCOUNT_ADDRESS_LENGTH => {
+            let mut payload = [0u8; 35];
+            decode_checked(address, &mut payload)?;
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&payload[1..33]);
+            match payload[0] {
+                ACCOUNT_VERSION_BYTE => Ok(StrKey::Account(key)),
+                CONTRACT_VERSION_BYTE => Ok(StrKey::Contract(key)),
+                _ => Err(StrKeyError::InvalidVersionByte),
+            }
+        }
+        MUXED_ADDRESS_LENGTH => {
+            let mut payload = [0u8; 43];
+            decode_checked(address, &mut payload)?;
+            if payload[0] != MUXED_VERSION_BYTE {
+                return Err(StrKeyError::InvalidVersionByte);
+            }
+            let mut ed25519 = [0u8; 32];
+            ed25519.copy_from_slice(&payload[1..33]);
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&payload[33..41]);
+            Ok(StrKey::Muxed {
+                ed25519,
+                id: u64::from_be_bytes(id),
+            })
+        }
+        _ => Err(StrKeyError::InvalidLength),
+    }
+}
+
+/// Encodes an ed25519 public key as a `G...` account address.
+pub fn encode_account(ed25519: &[u8; 32]) -> [u8; ACCOUNT_ADDRESS_LENGTH] {
+    let mut payload = [0u8; 35];
+    payload[0] = ACCOUNT_VERSION_BYTE;
+    payload[1..33].copy_from_slice(ed25519);
+    let checksum = crc16(&payload[..33]);
+    payload[33..].copy_from_slice(&checksum.to_le_bytes());
+
+    let mut address = [0u8; ACCOUNT_ADDRESS_LENGTH];
+    encode_base32(&payload, &mut address);
+    address
+}
+
+fn decode_checked(address: &[u8], payload: &mut [u8]) -> Result<(), StrKeyError> {
+    decode_base32(address, payload)?;
+    let (data, checksum) = payload.split_at(payload.len() - 2);
+    if crc16(data).to_le_bytes() != checksum {
+        return Err(StrKeyError::InvalidChecksum);
+    }
+    Ok(())
+}
+
+/// CRC16-XModem.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Decodes unpadded base32 into `output`, which has to be exactly as long as
+/// the decoded data. Unused trailing bits have to be zero.
+fn decode_base32(input: &[u8], output: &mut [u8]) -> Result<(), StrKeyError> {
+    if input.len() * 5 / 8 != output.len() {
+        return Err(StrKeyError::InvalidLength);
+    }
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut index = 0;
+    for &byte in input {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&c| c == byte)
+            .ok_or(StrKeyError::InvalidCharacter)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output[index] = (buffer >> bits) as u8;
+            index += 1;
+        }
+    }
+    if buffer & ((1 << bits) - 1) != 0 {
+        return Err(StrKeyError::InvalidPadding);
+    }
+    Ok(())
+}
+
+/// Encodes `input` as unpadded base32 into `output`.
+fn encode_base32(input: &[u8], output: &mut [u8]) {
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut index = 0;
+    for &byte in input {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output[index] = BASE32_ALPHABET[((buffer >> bits) & 31) as usize];
+            index += 1;
+        }
+    }
+    if bits > 0 {
+        output[index] = BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &[u8] = b"GA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVSGZ";
+    const CONTRACT: &[u8] = b"CA3D5KRYM6CB7OWQ6TWYRR3Z4T7GNZLKERYNZGGA5SOAOPIFY6YQGAXE";
+    const MUXED: &[u8] = b"MA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJUAAAAAAAAAAAACJUQ";
+    const MUXED_HIGH_ID: &[u8] =
+        b"MA7QYNF7SOWQ3GLR2BGMZEHXAVIRZA4KVWLTJJFC7MGXUA74P7UJVAAAAAAAAAAAAAJLK";
+
+    const ACCOUNT_KEY: [u8; 32] = [
+        0x3f, 0x0c, 0x34, 0xbf, 0x93, 0xad, 0x0d, 0x99, 0x71, 0xd0, 0x4c, 0xcc, 0x90, 0xf7, 0x05,
+        0x51, 0x1c, 0x83, 0x8a, 0xad, 0x97, 0x34, 0xa4, 0xa2, 0xfb, 0x0d, 0x7a, 0x03, 0xfc, 0x7f,
+        0xe8, 0x9a,
+    ];
+    const CONTRACT_KEY: [u8; 32] = [
+        0x36, 0x3e, 0xaa, 0x38, 0x67, 0x84, 0x1f, 0xba, 0xd0, 0xf4, 0xed, 0x88, 0xc7, 0x79, 0xe4,
+        0xfe, 0x66, 0xe5, 0x6a, 0x24, 0x70, 0xdc, 0x98, 0xc0, 0xec, 0x9c, 0x07, 0x3d, 0x05, 0xc7,
+        0xb1, 0x03,
+    ];
+
+    fn with_last(address: &[u8], last: u8) -> [u8; MUXED_ADDRESS_LENGTH] {
+        let mut changed = [0u8; MUXED_ADDRESS_LENGTH];
+        changed[..address.len()].copy_from_slice(address);
+        changed[address.len() - 1] = last;
+        changed
+    }
+
+    #[test]
+    fn test_decode_valid_addresses() {
+        assert_eq!(decode(ACCOUNT), Ok(StrKey::Account(ACCOUNT_KEY)));
+        assert_eq!(decode(CONTRACT), Ok(StrKey::Contract(CONTRACT_KEY)));
+        assert_eq!(
+            decode(MUXED),
+            Ok(StrKey::Muxed {
+                ed25519: ACCOUNT_KEY,
+                id: 0,
+            })
+        );
+        assert_eq!(
+            decode(MUXED_HIGH_ID),
+            Ok(StrKey::Muxed {
+                ed25519: ACCOUNT_KEY,
+                id: 9223372036854775808,
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_checksum() {
+        let account = with_last(ACCOUNT, b'A');
+        assert_eq!(
+            decode(&account[..ACCOUNT_ADDRESS_LENGTH]),
+            Err(StrKeyError::InvalidChecksum)
+        );
+        let muxed = with_last(MUXED, b'A');
+        assert_eq!(decode(&muxed), Err(StrKeyError::InvalidChecksum));
+    }
+
+    #[test]
+    fn test_decode_invalid_length_and_padding() {
+        assert_eq!(
+            decode(&ACCOUNT[..ACCOUNT_ADDRESS_LENGTH - 1]),
+            Err(StrKeyError::InvalidLength)
+        );
+        assert_eq!(decode(&MUXED[1..]), Err(StrKeyError::InvalidLength));
+        assert_eq!(decode(b""), Err(StrKeyError::InvalidLength));
+
+        // The last character of an `M...` address only carries 4 bits of data.
+        let muxed = with_last(MUXED, b'R');
+        assert_eq!(decode(&muxed), Err(StrKeyError::InvalidPadding));
+        let account = with_last(ACCOUNT, b'1');
+        assert_eq!(
+            decode(&account[..ACCOUNT_ADDRESS_LENGTH]),
+            Err(StrKeyError::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_version_byte() {
+        // A valid seed (`S...`) has the right length and checksum.
+        let seed = b"SBU2RRGLXH3E5CQHTD3ODLDF2BWDCYUSSBLLZ5GNW7JXHDIYKXZWHOKR";
+        assert_eq!(decode(seed), Err(StrKeyError::InvalidVersionByte));
+
+        // The payload of a `G...` address under the muxed length.
+        let mut payload = [0u8; 43];
+        payload[0] = ACCOUNT_VERSION_BYTE;
+        payload[1..33].copy_from_slice(&ACCOUNT_KEY);
+        let checksum = crc16(&payload[..41]);
+        payload[41..].copy_from_slice(&checksum.to_le_bytes());
+        let mut address = [0u8; MUXED_ADDRESS_LENGTH];
+        encode_base32(&payload, &mut address);
+        assert_eq!(decode(&address), Err(StrKeyError::InvalidVersionByte));
+    }
+
+    #[test]
+    fn test_encode_account_round_trip() {
+        assert_eq!(&encode_account(&ACCOUNT_KEY)[..], ACCOUNT);
+        let key = [7u8; 32];
+        assert_eq!(decode(&encode_account(&key)), Ok(StrKey::Account(key)));
+    }
+}
@@ -0,0 +1,13 @@
+/// Converts `amount` from `from_decimals` to `to_decimals`.
+///
+/// Returns the converted amount and the dust, in `from_decimals`, that had to be
+/// truncated to represent it with fewer decimals. Returns `None` on overflow.
+pub fn scale_amount(amount: u128, from_decimals: u32, to_decimals: u32) -> Option<(u128, u128)> {
+    if to_decimals >= from_decimals {
+        let factor = 10u128.checked_pow(to_decimals - from_decimals)?;
+        Some((amount.checked_mul(factor)?, 0))
+    } else {
+        let factor = 10u128.checked_pow(from_decimals - to_decimals)?;
+        Some((amount / factor, amount % factor))
+    }
+}
@@ -0,0 +1,64 @@
+use crate::decoder;
+use crate::encoder;
+use soroban_sdk::{contracttype, Bytes, Env, String, Vec};
+
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositBatch {
+    pub token_addresses: Vec<String>,
+    pub from: String,
+    pub to: String,
+    pub amounts: Vec<u128>,
+    pub data: Bytes,
+}
+
+impl DepositBatch {
+    pub fn new(
+        token_addresses: Vec<String>,
+        from: String,
+        to: String,
+        amounts: Vec<u128>,
+        data: Bytes,
+    ) -> Self {
+        Self {
+            token_addresses,
+            from,
+            to,
+            amounts,
+            data,
+        }
+    }
+
+    pub fn encode(&self, e: &Env, method: String) -> Bytes {
+        let mut list: Vec<Bytes> = Vec::new(e);
+        list.push_back(encoder::encode_string(e, method));
+        list.push_back(encoder::encode_strings(e, self.token_addresses.clone()));
+        list.push_back(encoder::encode_string(e, self.from.clone()));
+        list.push_back(encoder::encode_string(e, self.to.clone()));
+        list.push_back(encoder::encode_u128s(e, self.amounts.clone()));
+        list.push_back(encoder::encode(e, self.data.clone()));
+
+        encoder::encode_list(e, list, false)
+    }
+
+    pub fn decode(e: &Env, bytes: Bytes) -> DepositBatch {
+        let decoded = decoder::decode_list(e, bytes);
+        if decoded.len() != 6 {
+            panic!("InvalidRlpLength");
+        }
+
+        let token_addresses = decoder::decode_strings(e, decoded.get(1).unwrap());
+        let from = decoder::decode_string(e, decoded.get(2).unwrap());
+        let to = decoder::decode_string(e, decoded.get(3).unwrap());
+        let amounts = decoder::decode_u128s(e, decoded.get(4).unwrap());
+        let data = decoded.get(5).unwrap();
+
+        Self {
+            token_addresses,
+            from,
+            to,
+            amounts,
+            data,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::decoder;
+use crate::encoder;
+use soroban_sdk::{contracttype, Address, Bytes, Env, String, Vec};
+
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositBatchRevert {
+    pub token_addresses: Vec<Address>,
+    pub to: Address,
+    pub amounts: Vec<u128>,
+}
+
+impl DepositBatchRevert {
+    pub fn new(token_addresses: Vec<Address>, to: Address, amounts: Vec<u128>) -> Self {
+        Self {
+            token_addresses,
+            to,
+            amounts,
+        }
+    }
+
+    pub fn encode(&self, e: &Env, method: String) -> Bytes {
+        let mut token_addresses: Vec<String> = Vec::new(e);
+        for token_address in self.token_addresses.iter() {
+            token_addresses.push_back(token_address.to_string());
+        }
+
+        let mut list: Vec<Bytes> = Vec::new(e);
+        list.push_back(encoder::encode_string(e, method));
+        list.push_back(encoder::encode_strings(e, token_addresses));
+        list.push_back(encoder::encode_string(e, self.to.to_string()));
+        list.push_back(encoder::encode_u128s(e, self.amounts.clone()));
+
+        encoder::encode_list(e, list, false)
+    }
+
+    pub fn decode(e: &Env, bytes: Bytes) -> DepositBatchRevert {
+        let decoded = decoder::decode_list(e, bytes);
+        if decoded.len() != 4 {
+            panic!("InvalidRlpLength");
+        }
+
+        let mut token_addresses: Vec<Address> = Vec::new(e);
+        for token_address in decoder::decode_strings(e, decoded.get(1).unwrap()).iter() {
+            token_addresses.push_back(Address::from_string(&token_address));
+        }
+        let to = Address::from_string(&decoder::decode_string(e, decoded.get(2).unwrap()));
+        let amounts = decoder::decode_u128s(e, decoded.get(3).unwrap());
+
+        Self {
+            token_addresses,
+            to,
+            amounts,
+        }
+    }
+}
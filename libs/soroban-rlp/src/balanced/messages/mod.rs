@@ -1,5 +1,8 @@
 pub mod deposit_revert;
 pub mod deposit;
+pub mod deposit_batch;
+pub mod deposit_batch_revert;
+pub mod reserve_report;
 pub mod withdraw_to;
 pub mod cross_transfer;
 pub mod cross_transfer_revert;
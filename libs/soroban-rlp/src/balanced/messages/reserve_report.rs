@@ -0,0 +1,58 @@
+use crate::decoder;
+use crate::encoder;
+use soroban_sdk::{contracttype, Bytes, Env, String, Vec};
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveReport {
+    pub token_addresses: Vec<String>,
+    pub balances: Vec<u128>,
+    pub deposited: Vec<u128>,
+    pub withdrawn: Vec<u128>,
+}
+
+impl ReserveReport {
+    pub fn new(
+        token_addresses: Vec<String>,
+        balances: Vec<u128>,
+        deposited: Vec<u128>,
+        withdrawn: Vec<u128>,
+    ) -> Self {
+        Self {
+            token_addresses,
+            balances,
+            deposited,
+            withdrawn,
+        }
+    }
+
+    pub fn encode(&self, e: &Env, method: String) -> Bytes {
+        let mut list: Vec<Bytes> = Vec::new(e);
+        list.push_back(encoder::encode_string(e, method));
+        list.push_back(encoder::encode_strings(e, self.token_addresses.clone()));
+        list.push_back(encoder::encode_u128s(e, self.balances.clone()));
+        list.push_back(encoder::encode_u128s(e, self.deposited.clone()));
+        list.push_back(encoder::encode_u128s(e, self.withdrawn.clone()));
+
+        encoder::encode_list(e, list, false)
+    }
+
+    pub fn decode(e: &Env, bytes: Bytes) -> ReserveReport {
+        let decoded = decoder::decode_list(e, bytes);
+        if decoded.len() != 5 {
+            panic!("InvalidRlpLength");
+        }
+
+        let token_addresses = decoder::decode_strings(e, decoded.get(1).unwrap());
+        let balances = decoder::decode_u128s(e, decoded.get(2).unwrap());
+        let deposited = decoder::decode_u128s(e, decoded.get(3).unwrap());
+        let withdrawn = decoder::decode_u128s(e, decoded.get(4).unwrap());
+
+        Self {
+            token_addresses,
+            balances,
+            deposited,
+            withdrawn,
+        }
+    }
+}
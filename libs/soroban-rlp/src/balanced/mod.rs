@@ -1,2 +1,3 @@
 pub mod address_utils;
+pub mod decimal_utils;
 pub mod messages;
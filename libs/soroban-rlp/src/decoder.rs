@@ -150,3 +150,14 @@ pub fn decode_strings(env: &Env, bytes: Bytes) -> Vec<String> {
 
     strings
 }
+
+pub fn decode_u128s(env: &Env, bytes: Bytes) -> Vec<u128> {
+    let list = decode_list(env, bytes);
+
+    let mut values: Vec<u128> = vec![env];
+    for byte in list {
+        values.push_back(bytes_to_u128(byte))
+    }
+
+    values
+}
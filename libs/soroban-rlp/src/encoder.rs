@@ -90,3 +90,13 @@ pub fn encode_strings(env: &Env, values: Vec<String>) -> Bytes {
 
     encode_list(&env, list, false)
 }
+
+pub fn encode_u128s(env: &Env, values: Vec<u128>) -> Bytes {
+    let mut list: Vec<Bytes> = vec![env];
+
+    for value in values {
+        list.push_back(encode_u128(env, value));
+    }
+
+    encode_list(env, list, false)
+}